@@ -15,11 +15,17 @@ use eval::model::Invoke;
 use eval::util::cmp::TryOrd;
 use eval::value::{ArrayRepr, IntegerRepr, ObjectRepr, StringRepr};
 use super::conv::{int, str_};
+use super::strings::text_units;
 
 
 /// Compute the length of given value (an array or a string).
-pub fn len(value: Value) -> eval::Result {
-    eval1!((value: &String) -> Integer { value.chars().count() as IntegerRepr });
+///
+/// The length of a string is the number of its characters,
+/// or grapheme clusters if the context is set to use them.
+pub fn len(value: Value, context: &Context) -> eval::Result {
+    eval1!((value: &String) -> Integer {
+        text_units(value, context.uses_graphemes()).len() as IntegerRepr
+    });
     eval1!((value: &Array) -> Integer { value.len() as IntegerRepr });
     eval1!((value: &Object) -> Integer { value.len() as IntegerRepr });
     mismatch!("len"; ("string") | ("array") | ("object") => (value))
@@ -200,7 +206,7 @@ pub fn omit(keys: Value, from: Value) -> eval::Result {
         // build the resulting object by excluding those keys
         // TODO: if the number of keys to exclude is small, we'd be better off
         // just removing them from original object
-        let mut result = ObjectRepr::with_capacity(from.len() - keyset.len());
+        let mut result = ObjectRepr::with_capacity(from.len().saturating_sub(keyset.len()));
         for (key, value) in from {
             if !keyset.contains(&key) {
                 result.insert(key, value);
@@ -4,9 +4,9 @@ use std::io::Write;
 
 use csv;
 use regex;
-use rustc_serialize::json::{Json, ToJson};
 
 use eval::{self, Error, Value};
+use eval::value::{parse_int, parse_int_radix};
 use eval::value::{ArrayRepr, BooleanRepr, IntegerRepr, FloatRepr, RegexRepr, StringRepr};
 
 
@@ -34,6 +34,7 @@ pub fn array(value: Value) -> eval::Result {
 /// * API functions such as compact()
 pub fn bool(value: Value) -> eval::Result {
     match value {
+        Value::Empty => Ok(Value::Boolean(false)),
         Value::Boolean(_) => Ok(value),
         Value::Integer(i) => Ok(Value::Boolean(i != 0)),
         Value::Float(f) => Ok(Value::Boolean(f != 0.0)),
@@ -63,6 +64,40 @@ pub fn int(value: Value) -> eval::Result {
     }
 }
 
+/// Parse a string as integer in given base (between 2 and 36, where digits above 9 are letters).
+///
+/// For base 16, 8, and 2, the string may also have the "0x", "0o" or "0b" prefix.
+pub fn frombase(base: Value, value: Value) -> eval::Result {
+    let base = match base {
+        Value::Integer(b) if 2 <= b && b <= 36 => b as u32,
+        b => return Err(Error::new(&format!(
+            "frombase() requires a base between 2 and 36, got {:?}", b
+        ))),
+    };
+    match value {
+        Value::String(ref s) => parse_int_radix(s.trim(), base)
+            .ok_or_else(|| Error::new(&format!("invalid base {} integer value: {}", base, s)))
+            .map(Value::Integer),
+        _ => Err(Error::new(&format!(
+            "frombase() requires a string, got {}", value.typename()
+        ))),
+    }
+}
+
+/// Parse a string as integer literal, recognizing the base from its prefix:
+/// "0x" for hexadecimal, "0o" for octal, "0b" for binary, and none for decimal.
+pub fn parseint(value: Value) -> eval::Result {
+    match value {
+        Value::Integer(_) => Ok(value),
+        Value::String(ref s) => parse_int(s.trim())
+            .ok_or_else(|| Error::new(&format!("invalid integer literal: {}", s)))
+            .map(Value::Integer),
+        _ => Err(Error::new(&format!(
+            "parseint() requires a string, got {}", value.typename()
+        ))),
+    }
+}
+
 /// Convert a value to a float.
 pub fn float(value: Value) -> eval::Result {
     match value {
@@ -88,6 +123,7 @@ pub fn str_(value: Value) -> eval::Result {
         Value::Float(f) => Ok(Value::String(f.to_string())),
         Value::String(_) => Ok(value),
         Value::Regex(ref r) => Ok(Value::String(r.as_str().to_owned())),
+        Value::Time(ref t) => Ok(Value::String(t.to_rfc3339())),
         _ => Err(Error::new(
             &format!("cannot convert {} to string", value.typename())
         )),
@@ -212,16 +248,26 @@ pub fn csv(value: Value) -> eval::Result {
 
 /// Converts a value to or from JSON:
 /// * an array or object input is converted to JSON string
+///   (pretty-printed with given number of spaces for indentation, if provided)
 /// * a string input is parsed as JSON
-pub fn json(value: Value) -> eval::Result {
+///
+/// Order of keys in JSON objects is preserved in both directions.
+pub fn json(value: Value, indent: Option<Value>) -> eval::Result {
     if let Value::String(ref json_string) = value {
-        let json_obj = try!(Json::from_str(json_string)
-            .map_err(|e| Error::new(&format!("invalid JSON string: {}", e))));
-        return Ok(Value::from(json_obj));
+        return Value::from_json_str(json_string)
+            .map_err(|e| Error::new(&format!("invalid JSON string: {}", e)));
     }
 
-    eval1!((value: &Array) -> String { value.to_json().to_string() });
-    eval1!((value: &Object) -> String { value.to_json().to_string() });
+    let indent = match indent {
+        None => None,
+        Some(Value::Integer(i)) if i >= 0 => Some(i as usize),
+        Some(i) => return Err(Error::new(&format!(
+            "json() expects a non-negative integer indentation, got {:?}", i
+        ))),
+    };
+    if value.is_array() || value.is_object() {
+        return Ok(Value::String(value.to_json_string(indent)));
+    }
 
     Err(Error::new(&format!(
         "json() expects a JSON string, an object or array, got {}", value.typename()
@@ -0,0 +1,257 @@
+//! Encoding & escaping functions.
+//!
+//! Functions which encode binary data accept either a string (encoded as UTF-8)
+//! or an array of bytes, like the one returned by utf8().
+//! Conversely, the decoding functions return a string if the decoded data is valid UTF-8,
+//! and an array of bytes otherwise.
+
+use std::char;
+
+use rustc_serialize::base64::{self, FromBase64, ToBase64};
+use rustc_serialize::hex::{FromHex, ToHex};
+use rustc_serialize::json::Json;
+
+use eval::{self, Error, Value};
+use eval::value::IntegerRepr;
+
+
+// Binary-to-text encodings
+
+/// Encode data as base64.
+///
+/// The optional variant is either "standard" (the default),
+/// or "url" for the URL- and filename-safe alphabet without padding.
+pub fn b64encode(value: Value, variant: Option<Value>) -> eval::Result {
+    let config = match variant {
+        None => base64::STANDARD,
+        Some(Value::String(ref v)) if v == "standard" => base64::STANDARD,
+        Some(Value::String(ref v)) if v == "url" => base64::URL_SAFE,
+        Some(v) => return Err(Error::new(&format!(
+            "b64encode() expects \"standard\" or \"url\" variant, got {:?}", v
+        ))),
+    };
+    let data = try!(bytes("b64encode", value));
+    Ok(Value::String(data.to_base64(config)))
+}
+
+/// Decode base64-encoded data.
+/// Both the standard and URL-safe alphabets are accepted, and padding is optional.
+pub fn b64decode(value: Value) -> eval::Result {
+    if let Value::String(ref s) = value {
+        return s.from_base64()
+            .map(string_or_bytes)
+            .map_err(|e| Error::new(&format!("b64decode() got invalid base64: {}", e)));
+    }
+    mismatch!("b64decode"; ("string") => (value))
+}
+
+/// Encode data as a string of (lowercase) hexadecimal digits, two for every byte.
+pub fn hexencode(value: Value) -> eval::Result {
+    let data = try!(bytes("hexencode", value));
+    Ok(Value::String(data.to_hex()))
+}
+
+/// Decode data from a string of hexadecimal digits.
+pub fn hexdecode(value: Value) -> eval::Result {
+    if let Value::String(ref s) = value {
+        return s.from_hex()
+            .map(string_or_bytes)
+            .map_err(|e| Error::new(&format!("hexdecode() got invalid hex string: {}", e)));
+    }
+    mismatch!("hexdecode"; ("string") => (value))
+}
+
+
+// URLs
+
+/// Percent-encode the data so that it can be safely included in a URL,
+/// e.g. as a query string parameter.
+///
+/// Only the unreserved characters (ASCII alphanumerics and "-._~") are left as they are.
+pub fn urlencode(value: Value) -> eval::Result {
+    let data = try!(bytes("urlencode", value));
+    Ok(Value::String(percent_encode(&data)))
+}
+
+/// Decode a percent-encoded string, like a URL query string parameter.
+/// Plus signs are decoded as spaces.
+pub fn urldecode(value: Value) -> eval::Result {
+    if let Value::String(ref s) = value {
+        return percent_decode("urldecode", s).map(string_or_bytes);
+    }
+    mismatch!("urldecode"; ("string") => (value))
+}
+
+
+// Escaping
+
+/// Escape the characters which have special meaning in HTML: & < > " '
+pub fn htmlescape(value: Value) -> eval::Result {
+    eval1!(value : &String {{
+        let mut result = String::with_capacity(value.len());
+        for c in value.chars() {
+            match HTML_ENTITIES.iter().find(|&&(_, ch)| ch == c) {
+                Some(&(entity, _)) if c != NBSP => result.push_str(&format!("&{};", entity)),
+                _ => result.push(c),
+            }
+        }
+        result
+    }});
+    mismatch!("htmlescape"; ("string") => (value))
+}
+
+/// Replace the HTML character references in the string with the characters they represent.
+///
+/// Besides the basic named entities (like &amp;), numeric references are supported,
+/// both decimal (&#39;) and hexadecimal (&#x27;).
+/// Anything that's not a valid reference is left intact.
+pub fn htmlunescape(value: Value) -> eval::Result {
+    eval1!(value : &String {{
+        let mut result = String::with_capacity(value.len());
+        let mut rest: &str = value;
+        while let Some(amp) = rest.find('&') {
+            result.push_str(&rest[..amp]);
+            rest = &rest[amp..];
+            let reference = rest.find(';')
+                .and_then(|semi| html_reference(&rest[1..semi]).map(|c| (c, semi)));
+            match reference {
+                Some((c, semi)) => {
+                    result.push(c);
+                    rest = &rest[semi + 1..];
+                },
+                None => {
+                    result.push('&');
+                    rest = &rest[1..];
+                },
+            }
+        }
+        result.push_str(rest);
+        result
+    }});
+    mismatch!("htmlunescape"; ("string") => (value))
+}
+
+const NBSP: char = '\u{a0}';
+const HTML_ENTITIES: &'static [(&'static str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("#39", '\''),
+    ("apos", '\''),
+    ("nbsp", NBSP),
+];
+
+/// Quote the string so that it's treated as a single word by POSIX shells.
+///
+/// Strings consisting only of characters that are safe to use unquoted are left intact.
+pub fn shquote(value: Value) -> eval::Result {
+    eval1!(value : &String {
+        // (is_digit() with radix 36 accepts exactly the ASCII alphanumerics)
+        if !value.is_empty() &&
+                value.chars().all(|c| c.is_digit(36) || SHELL_SAFE_CHARS.contains(c)) {
+            value.clone()
+        } else {
+            format!("'{}'", value.replace("'", r#"'"'"'"#))
+        }
+    });
+    mismatch!("shquote"; ("string") => (value))
+}
+
+const SHELL_SAFE_CHARS: &'static str = "@%+=:,./-_";
+
+/// Quote the string as a JSON string literal, escaping any special characters.
+pub fn jsonquote(value: Value) -> eval::Result {
+    if let Value::String(ref s) = value {
+        return Ok(Value::String(Json::String(s.clone()).to_string()));
+    }
+    mismatch!("jsonquote"; ("string") => (value))
+}
+
+
+// Utility functions
+
+/// Interpret the value as binary data:
+/// either UTF-8 bytes of a string, or an array of byte values.
+pub fn bytes(name: &str, value: Value) -> Result<Vec<u8>, Error> {
+    match value {
+        Value::String(s) => Ok(s.into_bytes()),
+        Value::Array(a) => a.into_iter().map(|v| match v {
+            Value::Integer(i) if 0 <= i && i <= 255 => Ok(i as u8),
+            v => Err(Error::new(&format!(
+                "{}() expects an array of bytes (0-255), got an element {:?}", name, v
+            ))),
+        }).collect(),
+        v => mismatch!(name; ("string") | ("array") => (v)),
+    }
+}
+
+/// Percent-encode all the bytes of the data except for the unreserved URL characters.
+pub fn percent_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len());
+    for &b in data {
+        match b {
+            b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' | b'-' | b'.' | b'_' | b'~' =>
+                result.push(b as char),
+            _ => result.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    result
+}
+
+/// Decode a percent-encoded string (with plus signs standing for spaces) into bytes.
+pub fn percent_decode(name: &str, s: &str) -> Result<Vec<u8>, Error> {
+    let input = s.as_bytes();
+    let mut data = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'%' => {
+                let byte = if i + 3 <= input.len() {
+                    String::from_utf8_lossy(&input[i + 1..i + 3]).from_hex().ok()
+                } else {
+                    None
+                };
+                match byte {
+                    Some(b) => data.extend(b),
+                    None => return Err(Error::new(&format!(
+                        "{}() got invalid percent-encoding at position {}", name, i
+                    ))),
+                }
+                i += 3;
+                continue;
+            },
+            b'+' => data.push(b' '),
+            b => data.push(b),
+        }
+        i += 1;
+    }
+    Ok(data)
+}
+
+/// Convert decoded binary data into a string if it's valid UTF-8,
+/// or an array of bytes otherwise.
+fn string_or_bytes(data: Vec<u8>) -> Value {
+    match String::from_utf8(data) {
+        Ok(s) => Value::String(s),
+        Err(e) => Value::Array(e.into_bytes().into_iter()
+            .map(|b| Value::Integer(b as IntegerRepr)).collect()),
+    }
+}
+
+/// Decode the inside of an HTML character reference, e.g. "amp" or "#x27".
+fn html_reference(reference: &str) -> Option<char> {
+    if let Some(&(_, c)) = HTML_ENTITIES.iter().find(|&&(entity, _)| entity == reference) {
+        return Some(c);
+    }
+    if !reference.starts_with('#') {
+        return None;
+    }
+    let number = &reference[1..];
+    let code = if number.starts_with('x') || number.starts_with('X') {
+        u32::from_str_radix(&number[1..], 16)
+    } else {
+        number.parse::<u32>()
+    };
+    code.ok().and_then(char::from_u32)
+}
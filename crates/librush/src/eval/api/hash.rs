@@ -0,0 +1,142 @@
+//! Hash functions.
+//!
+//! Like the encoding functions, they accept either a string (hashed as UTF-8)
+//! or an array of bytes. The resulting digest is returned as a string of lowercase
+//! hexadecimal digits, or -- if the "bytes" format is requested -- as an array of bytes.
+
+use std::hash::Hasher;
+
+use crc::crc32;
+use fnv::FnvHasher;
+use hmac::{Hmac, Mac};
+use hmac::digest::KeyInit;
+use md5::Md5;
+use rustc_serialize::hex::ToHex;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use twox_hash::XxHash;
+
+use eval::{self, Error, Value};
+use eval::api::conv::str_;
+use eval::value::IntegerRepr;
+use super::encoding::bytes;
+
+
+// Cryptographic hashes
+
+/// Compute the MD5 digest of the data.
+pub fn md5(value: Value, format: Option<Value>) -> eval::Result {
+    digest::<Md5>("md5", value, format)
+}
+
+/// Compute the SHA-1 digest of the data.
+pub fn sha1(value: Value, format: Option<Value>) -> eval::Result {
+    digest::<Sha1>("sha1", value, format)
+}
+
+/// Compute the SHA-256 digest of the data.
+pub fn sha256(value: Value, format: Option<Value>) -> eval::Result {
+    digest::<Sha256>("sha256", value, format)
+}
+
+/// Compute the SHA-512 digest of the data.
+pub fn sha512(value: Value, format: Option<Value>) -> eval::Result {
+    digest::<Sha512>("sha512", value, format)
+}
+
+/// Compute the HMAC (keyed-hash message authentication code) of the data
+/// using given key and hash algorithm: "md5", "sha1", "sha256", or "sha512".
+///
+/// The result is always a hexadecimal string.
+pub fn hmac(key: Value, value: Value, algo: Value) -> eval::Result {
+    let key = try!(bytes("hmac", key));
+    let data = try!(bytes("hmac", value));
+    let code = match algo {
+        Value::String(ref a) if a == "md5" => hmac_code::<Hmac<Md5>>(&key, &data),
+        Value::String(ref a) if a == "sha1" => hmac_code::<Hmac<Sha1>>(&key, &data),
+        Value::String(ref a) if a == "sha256" => hmac_code::<Hmac<Sha256>>(&key, &data),
+        Value::String(ref a) if a == "sha512" => hmac_code::<Hmac<Sha512>>(&key, &data),
+        algo => return Err(Error::new(&format!(
+            "hmac() expects \"md5\", \"sha1\", \"sha256\", or \"sha512\" algorithm, got {:?}",
+            algo
+        ))),
+    };
+    Ok(Value::String(code.to_hex()))
+}
+
+
+// Non-cryptographic hashes
+
+/// Compute the CRC-32 checksum (as used by e.g. gzip) of the data.
+pub fn crc32(value: Value, format: Option<Value>) -> eval::Result {
+    let data = try!(bytes("crc32", value));
+    let checksum = crc32::checksum_ieee(&data);
+    digest_value("crc32", be_bytes(checksum as u64, 4), format)
+}
+
+/// Compute the 64-bit xxHash of the data.
+pub fn xxhash(value: Value, format: Option<Value>) -> eval::Result {
+    let data = try!(bytes("xxhash", value));
+    let mut hasher = XxHash::with_seed(0);
+    hasher.write(&data);
+    digest_value("xxhash", be_bytes(hasher.finish(), 8), format)
+}
+
+/// Compute the 64-bit FNV-1a hash of the data.
+pub fn fnv(value: Value, format: Option<Value>) -> eval::Result {
+    let data = try!(bytes("fnv", value));
+    digest_value("fnv", be_bytes(fnv_of(&data), 8), format)
+}
+
+/// Hash a value into a non-negative integer.
+///
+/// The result is stable across runs (and machines), which makes it useful
+/// for deterministic sampling and sharding, as in `hash(_) % 10 == 0`.
+/// Values other than strings are hashed through their string form.
+pub fn hash(value: Value) -> eval::Result {
+    let data = match value {
+        Value::String(s) => s.into_bytes(),
+        v => try!(str_(v)).unwrap_string().into_bytes(),
+    };
+    Ok(Value::Integer((fnv_of(&data) >> 1) as IntegerRepr))
+}
+
+
+// Utility functions
+
+/// Compute the digest of the data using given algorithm.
+fn digest<D: Digest>(name: &str, value: Value, format: Option<Value>) -> eval::Result {
+    let data = try!(bytes(name, value));
+    digest_value(name, D::digest(&data).to_vec(), format)
+}
+
+fn hmac_code<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut hmac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    hmac.update(data);
+    hmac.finalize().into_bytes().to_vec()
+}
+
+/// Convert the digest into a value of requested format: "hex" (the default) or "bytes".
+fn digest_value(name: &str, digest: Vec<u8>, format: Option<Value>) -> eval::Result {
+    match format {
+        None => Ok(Value::String(digest.to_hex())),
+        Some(Value::String(ref f)) if f == "hex" => Ok(Value::String(digest.to_hex())),
+        Some(Value::String(ref f)) if f == "bytes" => Ok(Value::Array(
+            digest.into_iter().map(|b| Value::Integer(b as IntegerRepr)).collect()
+        )),
+        Some(f) => Err(Error::new(&format!(
+            "{}() expects \"hex\" or \"bytes\" format, got {:?}", name, f
+        ))),
+    }
+}
+
+fn fnv_of(data: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(data);
+    hasher.finish()
+}
+
+/// Big-endian bytes of the lowest `len` bytes of given number.
+fn be_bytes(n: u64, len: usize) -> Vec<u8> {
+    (0..len).rev().map(|i| (n >> (8 * i)) as u8).collect()
+}
@@ -1,9 +1,11 @@
 //! Math functions.
 
+use std::cmp;
 use std::fmt::Display;
+use std::mem;
 
 use eval::{self, Error, Value};
-use eval::value::FloatRepr;
+use eval::value::{FloatRepr, IntegerRepr};
 
 
 /// Compute the absolute value of a number.
@@ -70,6 +72,103 @@ pub fn ln(value : Value) -> eval::Result {
     )))
 }
 
+/// Logarithm of a number with respect to given base.
+pub fn log(base: Value, value: Value) -> eval::Result {
+    let base = try!(float_arg("log", base));
+    if base <= 0.0 || base == 1.0 {
+        return Err(Error::new(&format!(
+            "log() requires a positive base other than 1, got {}", base
+        )));
+    }
+    Ok(Value::Float(try!(float_arg("log", value)).log(base)))
+}
+
+/// Binary logarithm (with respect to base 2).
+pub fn log2(value: Value) -> eval::Result {
+    map_float("log2", value, FloatRepr::log2)
+}
+
+/// Decimal logarithm (with respect to base 10).
+pub fn log10(value: Value) -> eval::Result {
+    map_float("log10", value, FloatRepr::log10)
+}
+
+/// Compute the length of the hypotenuse of a right triangle with given legs,
+/// i.e. sqrt(x*x + y*y), without an intermediate overflow.
+pub fn hypot(x: Value, y: Value) -> eval::Result {
+    let x = try!(float_arg("hypot", x));
+    let y = try!(float_arg("hypot", y));
+    Ok(Value::Float(x.hypot(y)))
+}
+
+
+// Trigonometry
+//
+// Angles are always in radians.
+
+/// Sine of an angle.
+pub fn sin(value: Value) -> eval::Result {
+    map_float("sin", value, FloatRepr::sin)
+}
+
+/// Cosine of an angle.
+pub fn cos(value: Value) -> eval::Result {
+    map_float("cos", value, FloatRepr::cos)
+}
+
+/// Tangent of an angle.
+pub fn tan(value: Value) -> eval::Result {
+    map_float("tan", value, FloatRepr::tan)
+}
+
+/// Arcsine of a number, in the range [-pi/2, pi/2].
+pub fn asin(value: Value) -> eval::Result {
+    map_float("asin", value, FloatRepr::asin)
+}
+
+/// Arccosine of a number, in the range [0, pi].
+pub fn acos(value: Value) -> eval::Result {
+    map_float("acos", value, FloatRepr::acos)
+}
+
+/// Arctangent of a number, in the range [-pi/2, pi/2].
+pub fn atan(value: Value) -> eval::Result {
+    map_float("atan", value, FloatRepr::atan)
+}
+
+/// Arctangent of y/x which takes the signs of both into account,
+/// returning the angle of the (x, y) point in the range [-pi, pi].
+pub fn atan2(y: Value, x: Value) -> eval::Result {
+    let y = try!(float_arg("atan2", y));
+    let x = try!(float_arg("atan2", x));
+    Ok(Value::Float(y.atan2(x)))
+}
+
+/// Hyperbolic sine of a number.
+pub fn sinh(value: Value) -> eval::Result {
+    map_float("sinh", value, FloatRepr::sinh)
+}
+
+/// Hyperbolic cosine of a number.
+pub fn cosh(value: Value) -> eval::Result {
+    map_float("cosh", value, FloatRepr::cosh)
+}
+
+/// Hyperbolic tangent of a number.
+pub fn tanh(value: Value) -> eval::Result {
+    map_float("tanh", value, FloatRepr::tanh)
+}
+
+/// Convert an angle from radians to degrees.
+pub fn degrees(value: Value) -> eval::Result {
+    map_float("degrees", value, FloatRepr::to_degrees)
+}
+
+/// Convert an angle from degrees to radians.
+pub fn radians(value: Value) -> eval::Result {
+    map_float("radians", value, FloatRepr::to_radians)
+}
+
 
 // Rounding
 
@@ -100,6 +199,45 @@ pub fn round(value : Value) -> eval::Result {
     )))
 }
 
+/// Round a number to given number of digits after the decimal point.
+///
+/// The number of digits can be negative, in which case the number
+/// is rounded to tens, hundreds, and so on.
+pub fn roundn(ndigits: Value, value: Value) -> eval::Result {
+    let ndigits = match ndigits {
+        Value::Integer(n) => cmp::max(-MAX_ROUND_DIGITS, cmp::min(n, MAX_ROUND_DIGITS)) as i32,
+        n => return Err(Error::new(&format!(
+            "roundn() requires an integer number of digits, got {}", n.typename()
+        ))),
+    };
+
+    if let Value::Integer(i) = value {
+        if ndigits >= 0 {
+            return Ok(Value::Integer(i));
+        }
+        let factor = (10 as FloatRepr).powi(-ndigits);
+        let result = (i as FloatRepr / factor).round() * factor;
+        if result.abs() >= IntegerRepr::max_value() as FloatRepr {
+            return Err(Error::new(&format!(
+                "roundn() of {} to {} digits is out of integer range", i, ndigits
+            )));
+        }
+        return Ok(Value::Integer(result as IntegerRepr));
+    }
+    let x = try!(float_arg("roundn", value));
+    let factor = (10 as FloatRepr).powi(ndigits);
+    let scaled = x * factor;
+    if !scaled.is_finite() {
+        // too many digits for the number to be affected by rounding
+        return Ok(Value::Float(x));
+    }
+    Ok(Value::Float(scaled.round() / factor))
+}
+
+/// Number of digits beyond which rounding is clamped,
+/// chosen so that the corresponding power of ten is still a (normal) float.
+const MAX_ROUND_DIGITS: IntegerRepr = 308;
+
 /// Return the integer part of the number (round towards zero).
 pub fn trunc(value : Value) -> eval::Result {
     eval1!(value : Integer { value });
@@ -110,6 +248,105 @@ pub fn trunc(value : Value) -> eval::Result {
 }
 
 
+/// Restrict a number to the [lo, hi] range.
+///
+/// The result is an integer if all the arguments are integers, and a float otherwise.
+pub fn clamp(lo: Value, hi: Value, value: Value) -> eval::Result {
+    if let (&Value::Integer(lo), &Value::Integer(hi), &Value::Integer(x)) = (&lo, &hi, &value) {
+        if lo > hi {
+            return Err(Error::new(&format!(
+                "clamp() requires lo <= hi, got {} and {}", lo, hi
+            )));
+        }
+        return Ok(Value::Integer(if x < lo { lo } else if x > hi { hi } else { x }));
+    }
+
+    let lo = try!(float_arg("clamp", lo));
+    let hi = try!(float_arg("clamp", hi));
+    let x = try!(float_arg("clamp", value));
+    if lo > hi {
+        return Err(Error::new(&format!(
+            "clamp() requires lo <= hi, got {} and {}", lo, hi
+        )));
+    }
+    Ok(Value::Float(x.max(lo).min(hi)))
+}
+
+
+// Number theory
+
+/// Divide two integers, returning both the quotient and the remainder
+/// as a two-element array.
+///
+/// Like the / and % operators, the quotient is rounded towards zero.
+pub fn divmod(left: Value, right: Value) -> eval::Result {
+    if let (&Value::Integer(a), &Value::Integer(b)) = (&left, &right) {
+        if b == 0 {
+            return Err(Error::new("divmod() by zero"));
+        }
+        return match (a.checked_div(b), a.checked_rem(b)) {
+            (Some(q), Some(r)) => Ok(Value::Array(vec![Value::Integer(q), Value::Integer(r)])),
+            _ => Err(Error::new(&format!("divmod() of {} by {} overflows", a, b))),
+        };
+    }
+    mismatch!("divmod"; ("integer", "integer") => (left, right))
+}
+
+/// Compute the greatest common divisor of two integers.
+pub fn gcd(left: Value, right: Value) -> eval::Result {
+    if let (&Value::Integer(a), &Value::Integer(b)) = (&left, &right) {
+        return gcd_of(a, b).map(Value::Integer)
+            .ok_or_else(|| Error::new(&format!("gcd() of {} and {} overflows", a, b)));
+    }
+    mismatch!("gcd"; ("integer", "integer") => (left, right))
+}
+
+/// Compute the least common multiple of two integers.
+pub fn lcm(left: Value, right: Value) -> eval::Result {
+    if let (&Value::Integer(a), &Value::Integer(b)) = (&left, &right) {
+        if a == 0 || b == 0 {
+            return Ok(Value::Integer(0));
+        }
+        return gcd_of(a, b)
+            .and_then(|d| (a / d).checked_mul(b))
+            .and_then(checked_abs)
+            .map(Value::Integer)
+            .ok_or_else(|| Error::new(&format!("lcm() of {} and {} overflows", a, b)));
+    }
+    mismatch!("lcm"; ("integer", "integer") => (left, right))
+}
+
+/// Check whether an integer is a prime number.
+pub fn isprime(value: Value) -> eval::Result {
+    eval1!((value: Integer) -> Boolean {
+        value >= 2 && (2..).take_while(|&d| d <= value / d).all(|d| value % d != 0)
+    });
+    mismatch!("isprime"; ("integer") => (value))
+}
+
+/// Compute the factorial of a non-negative integer.
+pub fn factorial(value: Value) -> eval::Result {
+    if let Value::Integer(n) = value {
+        if n < 0 {
+            return Err(Error::new(&format!(
+                "factorial() requires a non-negative integer, got {}", n
+            )));
+        }
+        if n > MAX_FACTORIAL {
+            return Err(Error::new(&format!("factorial() of {} is too large", n)));
+        }
+        return (1..n + 1)
+            .fold(Some(1 as IntegerRepr), |acc, i| acc.and_then(|a| a.checked_mul(i)))
+            .map(Value::Integer)
+            .ok_or_else(|| Error::new(&format!("factorial() of {} is too large", n)));
+    }
+    mismatch!("factorial"; ("integer") => (value))
+}
+
+/// Largest number whose factorial fits in an integer.
+const MAX_FACTORIAL: IntegerRepr = 20;
+
+
 // Numeric bases
 
 /// Convert an integer to a binary string.
@@ -135,3 +372,39 @@ pub fn hex(value: Value) -> eval::Result {
         "hex() requires a number, got {}", value.typename()
     )))
 }
+
+
+// Utility functions
+
+/// Interpret a numeric value as float.
+fn float_arg(name: &str, value: Value) -> Result<FloatRepr, Error> {
+    match value {
+        Value::Integer(i) => Ok(i as FloatRepr),
+        Value::Float(f) => Ok(f),
+        v => Err(Error::new(&format!(
+            "{}() requires a number, got {}", name, v.typename()
+        ))),
+    }
+}
+
+/// Apply a float function to a numeric value.
+fn map_float(name: &str, value: Value, func: fn(FloatRepr) -> FloatRepr) -> eval::Result {
+    float_arg(name, value).map(func).map(Value::Float)
+}
+
+/// Compute the greatest common divisor of two integers,
+/// unless it (or any intermediate result) overflows.
+fn gcd_of(mut a: IntegerRepr, mut b: IntegerRepr) -> Option<IntegerRepr> {
+    while b != 0 {
+        a = match a.checked_rem(b) {
+            Some(r) => r,
+            None => return None,
+        };
+        mem::swap(&mut a, &mut b);
+    }
+    checked_abs(a)
+}
+
+fn checked_abs(n: IntegerRepr) -> Option<IntegerRepr> {
+    if n == IntegerRepr::min_value() { None } else { Some(n.abs()) }
+}
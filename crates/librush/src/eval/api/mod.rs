@@ -5,11 +5,17 @@
 
 pub mod base;
 pub mod conv;
+pub mod encoding;
 pub mod functools;
+pub mod hash;
 pub mod itertools;
 pub mod math;
+pub mod objects;
 pub mod random;
+pub mod stats;
 pub mod strings;
+pub mod time;
+pub mod url;
 
 
 use std::f64;
@@ -32,47 +38,129 @@ impl<'c> Context<'c> {
         // Keep the list sorted alphabetically by function names.
         //
         self.define_unary(          "abs",      math::abs           );
+        self.define_unary(          "acos",     math::acos          );
         self.define_binary(         "after",    strings::after      );
         self.define_unary(          "all",      itertools::all      );
         self.define_unary(          "any",      itertools::any      );
         self.define_unary(          "array",    conv::array         );
+        self.define_unary(          "asin",     math::asin          );
+        self.define_unary(          "atan",     math::atan          );
+        self.define_binary(         "atan2",    math::atan2         );
+        self.define_unary(          "b64decode", encoding::b64decode );
+        self.define_binary_opt(     "b64encode", encoding::b64encode );
         self.define_binary(         "before",   strings::before     );
         self.define_unary(          "bin",      math::bin           );
         self.define_unary(          "bool",     conv::bool          );
+        self.define_unary(          "camel",    strings::camel      );
+        self.define_unary(          "capitalize", strings::capitalize );
+        self.define_binary(         "captures", strings::captures   );
+        self.define_unary(          "casefold", strings::casefold   );
+        self.define_unary(          "category", strings::category   );
         self.define_unary(          "ceil",     math::ceil          );
+        self.define_binary(         "center",   strings::center     );
         self.define_unary(          "char",     strings::chr        );
         self.define_unary(          "chars",    strings::chars      );
         self.define_unary(          "chr",      strings::chr        );
+        self.define_ternary(        "clamp",    math::clamp         );
         self.define_unary(          "compact",  itertools::compact  );
+        self.define_unary(          "constant", strings::constant   );
+        self.define_binary(         "correlation", stats::correlation  );
+        self.define_unary(          "cos",      math::cos           );
+        self.define_unary(          "cosh",     math::cosh          );
+        self.define_binary_opt(     "crc32",    hash::crc32         );
         self.define_unary(          "csv",      conv::csv           );
         self.define_unary(          "deburr",   strings::deburr     );
+        self.define_unary(          "dedent",   strings::dedent     );
+        self.define_binary(         "deepmerge", objects::deepmerge  );
+        self.define_unary(          "degrees",  math::degrees       );
+        self.define_binary(         "divmod",   math::divmod        );
+        self.define_unary(          "entries",  objects::entries    );
+        self.define_unary(          "epoch",    time::epoch         );
         self.define_unary(          "exp",      math::exp           );
+        self.define_unary(          "factorial", math::factorial     );
         self.define_binary_ctx(     "filter",   functools::filter   );
+        self.define_binary_ctx(     "filterkeys", objects::filterkeys );
         self.define_unary(          "flip",     functools::flip     );
         self.define_unary(          "float",    conv::float         );
         self.define_unary(          "floor",    math::floor         );
+        self.define_binary_opt(     "fnv",      hash::fnv           );
         self.define_ternary_ctx(    "fold",     functools::reduce   );
         self.define_ternary_ctx(    "foldl",    functools::reduce   );
         self.define_binary(         "format",   strings::format_    );
+        self.define_binary(         "frombase", conv::frombase      );
+        self.define_unary(          "fromentries", objects::fromentries );
+        self.define_unary(          "fromepoch", time::fromepoch    );
+        self.define_unary(          "fromquerystring", url::fromquerystring );
+        self.define_binary(         "gcd",      math::gcd           );
+        self.define_ternary(        "get",      objects::get        );
+        self.define_unary(          "glen",     strings::glen       );
+        self.define_unary(          "graphemes", strings::graphemes  );
         self.define_ternary_ctx(    "gsub",     strings::sub        );
+        self.define_binary(         "has",      objects::has        );
+        self.define_unary(          "hash",     hash::hash          );
         self.define_unary(          "hex",      math::hex           );
+        self.define_unary(          "hexdecode", encoding::hexdecode );
+        self.define_unary(          "hexencode", encoding::hexencode );
+        self.define_binary(         "histogram", stats::histogram    );
+        self.define_ternary(        "hmac",     hash::hmac          );
+        self.define_unary(          "htmlescape", encoding::htmlescape );
+        self.define_unary(          "htmlunescape", encoding::htmlunescape );
+        self.define_binary(         "hypot",    math::hypot         );
         self.define_unary(          "id",       functools::identity );
+        self.define_binary(         "indent",   strings::indent     );
         self.define_binary(         "index",    base::index         );
         self.define_unary(          "int",      conv::int           );
+        self.define_unary(          "isalnum",  strings::isalnum    );
+        self.define_unary(          "isalpha",  strings::isalpha    );
+        self.define_unary(          "isdigit",  strings::isdigit    );
+        self.define_unary(          "islower",  strings::islower    );
+        self.define_unary(          "isprime",  math::isprime       );
+        self.define_unary(          "ispunct",  strings::ispunct    );
+        self.define_unary(          "isspace",  strings::isspace    );
+        self.define_unary(          "isupper",  strings::isupper    );
         self.define_binary(         "join",     strings::join       );
-        self.define_unary(          "json",     conv::json          );
+        self.define_binary_opt(     "json",     conv::json          );
+        self.define_unary(          "jsonquote", encoding::jsonquote );
+        self.define_unary(          "kebab",    strings::kebab      );
         self.define_unary(          "keys",     base::keys          );
         self.define_unary(          "latin1",   strings::latin1     );
-        self.define_unary(          "len",      base::len           );
+        self.define_binary(         "lcm",      math::lcm           );
+        self.define_unary_ctx(      "len",      base::len           );
         self.define_unary(          "lines",    strings::lines      );
         self.define_unary(          "ln",       math::ln            );
+        self.define_binary(         "log",      math::log           );
+        self.define_unary(          "log10",    math::log10         );
+        self.define_unary(          "log2",     math::log2          );
+        self.define_unary(          "lower",    strings::lower      );
+        self.define_binary(         "lpad",     strings::lpad       );
         self.define_binary_ctx(     "map",      functools::map      );
+        self.define_binary_ctx(     "mapkeys",  objects::mapkeys    );
+        self.define_binary_ctx(     "mapvalues", objects::mapvalues  );
+        self.define_binary(         "match",    strings::match_     );
+        self.define_binary(         "matchall", strings::matchall   );
         self.define_unary_ctx(      "max",      itertools::max      );
+        self.define_binary_opt(     "md5",      hash::md5           );
+        self.define_unary(          "mean",     stats::mean         );
+        self.define_unary(          "median",   stats::median       );
+        self.define_binary(         "merge",    objects::merge      );
         self.define_unary_ctx(      "min",      itertools::min      );
+        self.define_unary(          "mode",     stats::mode         );
+        self.define_unary(          "nfc",      strings::nfc        );
+        self.define_unary(          "nfd",      strings::nfd        );
+        self.define_unary(          "nfkc",     strings::nfkc       );
+        self.define_unary(          "nfkd",     strings::nfkd       );
+        self.define_nullary(        "now",      time::now           );
         self.define_unary(          "oct",      math::oct           );
         self.define_binary(         "omit",     base::omit          );
         self.define_unary(          "ord",      strings::ord        );
+        self.define_unary(          "parseint", conv::parseint      );
+        self.define_binary(         "partition", strings::partition  );
+        self.define_unary(          "pascal",   strings::pascal     );
+        self.define_binary(         "percentile", stats::percentile   );
         self.define_binary(         "pick",     base::pick          );
+        self.define_binary(         "quantiles", stats::quantiles    );
+        self.define_unary(          "querystring", url::querystring    );
+        self.define_unary(          "radians",  math::radians       );
         self.define_nullary(        "rand",     random::rand_       );
         self.define_unary(          "re",       conv::regex         );
         self.define_ternary_ctx(    "reduce",   functools::reduce   );
@@ -82,34 +170,82 @@ impl<'c> Context<'c> {
         self.define_unary(          "rev",      base::rev           );
         self.define_unary(          "rot13",    strings::rot13      );
         self.define_unary(          "round",    math::round         );
+        self.define_binary(         "roundn",   math::roundn        );
+        self.define_binary(         "rpad",     strings::rpad       );
+        self.define_binary(         "rpartition", strings::rpartition );
+        self.define_ternary(        "rsplitn",  strings::rsplitn    );
         self.define_ternary(        "rsub1",    strings::rsub1      );
         self.define_binary(         "sample",   random::sample      );
+        self.define_ternary(        "setin",    objects::setin      );
         self.define_unary(          "sgn",      math::sgn           );
+        self.define_binary_opt(     "sha1",     hash::sha1          );
+        self.define_binary_opt(     "sha256",   hash::sha256        );
+        self.define_binary_opt(     "sha512",   hash::sha512        );
+        self.define_unary(          "shlex",    strings::shlex      );
+        self.define_unary(          "shquote",  encoding::shquote   );
         self.define_unary(          "shuffle",  random::shuffle     );
+        self.define_unary(          "sin",      math::sin           );
+        self.define_unary(          "sinh",     math::sinh          );
+        self.define_unary(          "snake",    strings::snake      );
         self.define_unary(          "sort",     base::sort          );
         self.define_binary_ctx(     "sortby",   base::sort_by       );
+        self.define_unary(          "sortkeys", objects::sortkeys   );
         self.define_binary(         "split",    strings::split      );
+        self.define_binary_opt(     "splitlines", strings::splitlines );
+        self.define_ternary(        "splitn",   strings::splitn     );
         self.define_unary(          "sqrt",     math::sqrt          );
+        self.define_unary(          "stddev",   stats::stddev       );
         self.define_unary(          "str",      conv::str_          );
+        self.define_binary(         "strftime", time::strftime      );
         self.define_unary(          "string",   conv::str_          );
+        self.define_binary(         "strptime", time::strptime      );
         self.define_ternary_ctx(    "sub",      strings::sub        );
         self.define_ternary_ctx(    "sub1",     strings::sub1       );
         self.define_unary_ctx(      "sum",      itertools::sum      );
+        self.define_binary_opt(     "table",    strings::table      );
+        self.define_unary(          "tan",      math::tan           );
+        self.define_unary(          "tanh",     math::tanh          );
+        self.define_unary(          "title",    strings::title      );
         self.define_unary(          "trim",     strings::trim       );
         self.define_unary(          "trunc",    math::trunc         );
+        self.define_ternary(        "truncate", strings::truncate   );
+        self.define_binary(         "tz",       time::tz            );
+        self.define_unary(          "upper",    strings::upper      );
+        self.define_unary(          "url",      url::url            );
+        self.define_unary(          "urlbuild", url::urlbuild       );
+        self.define_unary(          "urldecode", encoding::urldecode );
+        self.define_unary(          "urlencode", encoding::urlencode );
         self.define_unary(          "utf8",     strings::utf8       );
         self.define_unary(          "values",   base::values        );
+        self.define_unary(          "variance", stats::variance     );
+        self.define_unary(          "width",    strings::width      );
         self.define_unary(          "words",    strings::words      );
+        self.define_binary(         "wrap",     strings::wrap       );
+        self.define_binary_opt(     "xxhash",   hash::xxhash        );
+        self.define_unary(          "zscore",   stats::zscore       );
     }
 
     fn init_constants(&mut self) {
-        //
-        // Keep the list sorted alphabetically by constant names (ignore case).
-        //
-        self.set("pi", Value::Float(f64::consts::PI as FloatRepr));
+        for &(name, value) in CONSTANTS {
+            self.set(name, Value::Float(value));
+        }
     }
 }
 
+/// Built-in constants.
+///
+/// Keep the list sorted alphabetically by constant names (ignore case).
+const CONSTANTS: &'static [(&'static str, FloatRepr)] = &[
+    ("inf", f64::INFINITY as FloatRepr),
+    ("pi", f64::consts::PI as FloatRepr),
+    ("tau", 2.0 * f64::consts::PI as FloatRepr),
+];
+
+/// Check whether given value is that of the built-in constant with given name.
+pub fn is_constant(name: &str, value: &Value) -> bool {
+    CONSTANTS.iter().any(|&(n, v)| n == name && *value == Value::Float(v))
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,291 @@
+//! Object API functions.
+
+use std::mem;
+
+use eval::{self, Context, Error, Value};
+use eval::model::Invoke;
+use eval::value::{ArrayRepr, IntegerRepr, ObjectRepr, StringRepr};
+use super::conv::{bool, int, str_};
+
+
+/// Check whether the object has an attribute with given key.
+pub fn has(key: Value, object: Value) -> eval::Result {
+    eval2!((key: &String, object: &Object) -> Boolean { object.contains_key(key) });
+    mismatch!("has"; ("string", "object") => (key, object))
+}
+
+
+/// Merge two objects together.
+///
+/// Result contains attributes of both objects. If a key exists in both,
+/// the value from the second object is used (but the key's position
+/// is the one from the first object).
+///
+/// This is also what the `+` operator does on two objects.
+pub fn merge(left: Value, right: Value) -> eval::Result {
+    if left.is_object() && right.is_object() {
+        let mut result = left.unwrap_object();
+        for (k, v) in right.unwrap_object() {
+            set_attr(&mut result, k, v);
+        }
+        return Ok(Value::Object(result));
+    }
+    mismatch!("merge"; ("object", "object") => (left, right))
+}
+
+/// Merge two objects together recursively.
+///
+/// This works like merge() except when both objects have an object value
+/// under the same key. Those nested objects are then merged, too.
+pub fn deepmerge(left: Value, right: Value) -> eval::Result {
+    if left.is_object() && right.is_object() {
+        let mut result = left.unwrap_object();
+        for (k, v) in right.unwrap_object() {
+            let merged = match take_attr(&mut result, &k) {
+                Some(old) => if old.is_object() && v.is_object() {
+                    try!(deepmerge(old, v))
+                } else {
+                    v
+                },
+                None => v,
+            };
+            set_attr(&mut result, k, merged);
+        }
+        return Ok(Value::Object(result));
+    }
+    mismatch!("deepmerge"; ("object", "object") => (left, right))
+}
+
+
+/// Convert an object into an array of its [key, value] pairs.
+pub fn entries(object: Value) -> eval::Result {
+    eval1!((object: Object) -> Array {
+        object.into_iter()
+            .map(|(k, v)| Value::Array(vec![Value::String(k), v]))
+            .collect()
+    });
+    mismatch!("entries"; ("object") => (object))
+}
+
+/// Create an object from an array of [key, value] pairs.
+/// This is the opposite of entries().
+pub fn fromentries(array: Value) -> eval::Result {
+    if let Value::Array(array) = array {
+        let mut result = ObjectRepr::with_capacity(array.len());
+        for entry in array {
+            let mut pair = match entry {
+                Value::Array(ref a) if a.len() == 2 => a.clone(),
+                _ => return Err(Error::new(&format!(
+                    "fromentries() expects an array of [key, value] pairs, got {:?}", entry
+                ))),
+            };
+            let value = pair.pop().unwrap();
+            let key = try!(str_(pair.pop().unwrap())).unwrap_string();
+            set_attr(&mut result, key, value);
+        }
+        return Ok(Value::Object(result));
+    }
+    mismatch!("fromentries"; ("array") => (array))
+}
+
+
+/// Map a function over the object's values.
+/// Returns the object with the same keys and values transformed by the function.
+pub fn mapvalues(func: Value, object: Value, context: &Context) -> eval::Result {
+    if let (&Value::Function(ref f), &Value::Object(ref o)) = (&func, &object) {
+        let mut result = ObjectRepr::with_capacity(o.len());
+        for (k, v) in o.iter() {
+            let context = Context::with_parent(context);
+            let mapped = try!(f.invoke1(v.clone(), &context));
+            result.insert(k.clone(), mapped);
+        }
+        return Ok(Value::Object(result));
+    }
+    mismatch!("mapvalues"; ("function", "object") => (func, object))
+}
+
+/// Map a function over the object's keys.
+/// The function has to return strings, which become the keys in the resulting object.
+pub fn mapkeys(func: Value, object: Value, context: &Context) -> eval::Result {
+    if let (&Value::Function(ref f), &Value::Object(ref o)) = (&func, &object) {
+        let mut result = ObjectRepr::with_capacity(o.len());
+        for (k, v) in o.iter() {
+            let context = Context::with_parent(context);
+            let mapped = try!(
+                f.invoke1(Value::String(k.clone()), &context).and_then(str_)
+            ).unwrap_string();
+            set_attr(&mut result, mapped, v.clone());
+        }
+        return Ok(Value::Object(result));
+    }
+    mismatch!("mapkeys"; ("function", "object") => (func, object))
+}
+
+/// Filter the object's attributes through a predicate function applied to their keys.
+/// Returns the object with only those attributes for which the predicate returned a truthy value.
+pub fn filterkeys(func: Value, object: Value, context: &Context) -> eval::Result {
+    if let (&Value::Function(ref f), &Value::Object(ref o)) = (&func, &object) {
+        let mut result = ObjectRepr::new();
+        for (k, v) in o.iter() {
+            let context = Context::with_parent(context);
+            let keep = try!(
+                f.invoke1(Value::String(k.clone()), &context).and_then(bool)
+            ).unwrap_bool();
+            if keep {
+                result.insert(k.clone(), v.clone());
+            }
+        }
+        return Ok(Value::Object(result));
+    }
+    mismatch!("filterkeys"; ("function", "object") => (func, object))
+}
+
+
+/// Sort the keys of an object, as well as those of any objects nested inside it
+/// (including objects inside of arrays).
+///
+/// Since objects otherwise retain the order in which their keys were inserted,
+/// this can be used to obtain a canonical JSON representation of the value.
+pub fn sortkeys(value: Value) -> eval::Result {
+    argcheck!("sortkeys"; ("object") | ("array") => (value));
+    Ok(sort_keys(value))
+}
+
+/// Retrieve a value from inside of nested objects and/or arrays.
+///
+/// The path can be given either as a dotted string (e.g. "foo.bar.0")
+/// or as an array of keys and indices (e.g. ["foo", "bar", 0]).
+/// If the path doesn't lead to any value, the default is returned.
+pub fn get(path: Value, value: Value, default: Value) -> eval::Result {
+    let path_type = path.typename();
+    let path = match path_segments(path) {
+        Some(p) => p,
+        None => return Err(Error::new(&format!(
+            "get() expects a string or array path, got {}", path_type
+        ))),
+    };
+
+    let mut current = &value;
+    for segment in path {
+        current = match child(current, segment) {
+            Some(v) => v,
+            None => return Ok(default),
+        };
+    }
+    Ok(current.clone())
+}
+
+/// Set a value inside of nested objects and/or arrays.
+///
+/// The path is given the same way as for get(). Any missing objects along the path
+/// are created, but array indices have to be within bounds.
+///
+/// Returns the modified value.
+pub fn setin(path: Value, value: Value, target: Value) -> eval::Result {
+    let path_type = path.typename();
+    let path = match path_segments(path) {
+        Some(p) => p,
+        None => return Err(Error::new(&format!(
+            "setin() expects a string or array path, got {}", path_type
+        ))),
+    };
+    set_path(target, &path, value)
+}
+
+
+// Utility functions
+
+/// Set an object attribute.
+/// Unlike a plain insert, this retains the position of the key if it already exists.
+pub fn set_attr(object: &mut ObjectRepr, key: StringRepr, value: Value) {
+    if let Some(existing) = object.get_mut(&key) {
+        *existing = value;
+        return;
+    }
+    object.insert(key, value);
+}
+
+/// Take the value of an object attribute out, leaving nil in its place
+/// (so that the key retains its position when set again).
+fn take_attr(object: &mut ObjectRepr, key: &str) -> Option<Value> {
+    object.get_mut(key).map(|v| mem::replace(v, Value::Empty))
+}
+
+/// Recursively sort the keys of all objects within given value.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(o) => {
+            let mut attrs: Vec<_> = o.into_iter().collect();
+            attrs.sort_by(|&(ref a, _), &(ref b, _)| a.cmp(b));
+            Value::Object(attrs.into_iter().map(|(k, v)| (k, sort_keys(v))).collect())
+        },
+        Value::Array(a) => Value::Array(a.into_iter().map(sort_keys).collect()),
+        v => v,
+    }
+}
+
+/// Split the path given to get() or setin() into its segments.
+fn path_segments(path: Value) -> Option<ArrayRepr> {
+    match path {
+        Value::String(ref s) if s.is_empty() => Some(vec![]),
+        Value::String(s) => Some(
+            s.split('.').map(StringRepr::from).map(Value::String).collect()
+        ),
+        Value::Array(a) => Some(a),
+        _ => None,
+    }
+}
+
+/// Retrieve the value of an object attribute or array element
+/// pointed to by a single path segment.
+fn child(value: &Value, segment: Value) -> Option<&Value> {
+    match *value {
+        Value::Object(ref o) => str_(segment).ok()
+            .and_then(|key| o.get(key.as_string())),
+        Value::Array(ref a) => int(segment).ok()
+            .and_then(|i| resolve_index(i.unwrap_int(), a.len()))
+            .map(|i| &a[i]),
+        _ => None,
+    }
+}
+
+/// Recursively set the value at given path, returning the modified target.
+fn set_path(target: Value, path: &[Value], value: Value) -> eval::Result {
+    let (segment, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Ok(value),
+    };
+
+    match target {
+        Value::Empty => set_path(Value::Object(ObjectRepr::new()), path, value),
+        Value::Object(mut o) => {
+            let key = try!(str_(segment.clone())).unwrap_string();
+            let child = take_attr(&mut o, &key).unwrap_or(Value::Empty);
+            let child = try!(set_path(child, rest, value));
+            set_attr(&mut o, key, child);
+            Ok(Value::Object(o))
+        },
+        Value::Array(mut a) => {
+            let index = try!(int(segment.clone())).unwrap_int();
+            let index = try!(resolve_index(index, a.len()).ok_or_else(|| Error::new(
+                &format!("array index out of range: {}", index)
+            )));
+            let child = mem::replace(&mut a[index], Value::Empty);
+            a[index] = try!(set_path(child, rest, value));
+            Ok(Value::Array(a))
+        },
+        _ => Err(Error::new(&format!(
+            "setin() cannot set {:?} inside of {}", segment, target.typename()
+        ))),
+    }
+}
+
+/// Resolve a possibly negative index into a position within a collection of given size.
+fn resolve_index(index: IntegerRepr, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as IntegerRepr + index } else { index };
+    if 0 <= index && index < len as IntegerRepr {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
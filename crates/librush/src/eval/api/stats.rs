@@ -0,0 +1,307 @@
+//! Statistical functions.
+//!
+//! They operate on arrays of numbers, where integers and floats can be mixed freely.
+//! Aggregates of an empty array are nil, like the result of sum().
+
+use std::cmp::Ordering;
+use std::f64;
+
+use eval::{self, Error, Value};
+use eval::value::{ArrayRepr, FloatRepr, IntegerRepr, ObjectRepr};
+
+
+/// Compute the arithmetic mean of an array of numbers.
+pub fn mean(array: Value) -> eval::Result {
+    let xs = try!(numbers("mean", array));
+    if xs.is_empty() {
+        return Ok(Value::Empty);
+    }
+    Ok(Value::Float(mean_of(&xs)))
+}
+
+/// Compute the median of an array of numbers.
+///
+/// For an even number of elements, the result is the mean of the middle two.
+pub fn median(array: Value) -> eval::Result {
+    let sorted = try!(sorted_numbers("median", array));
+    let len = sorted.len();
+    Ok(match len {
+        0 => Value::Empty,
+        _ if len % 2 == 1 => sorted[len / 2].1.clone(),
+        _ => Value::Float((sorted[len / 2 - 1].0 + sorted[len / 2].0) / 2.0),
+    })
+}
+
+/// Return the most common element of an array.
+/// If there are several of them, the one that occurs first is returned.
+pub fn mode(array: Value) -> eval::Result {
+    let array_type = array.typename();
+    if let Value::Array(array) = array {
+        let mut counts: Vec<(Value, usize)> = Vec::new();
+        for item in array {
+            match counts.iter().position(|&(ref v, _)| *v == item) {
+                Some(i) => counts[i].1 += 1,
+                None => counts.push((item, 1)),
+            }
+        }
+        let max_count = counts.iter().map(|&(_, c)| c).max().unwrap_or(0);
+        return Ok(counts.into_iter()
+            .find(|&(_, c)| c == max_count).map(|(v, _)| v)
+            .unwrap_or(Value::Empty));
+    }
+    Err(Error::new(&format!("mode() requires an array, got {}", array_type)))
+}
+
+
+/// Compute the p-th percentile (0 <= p <= 100) of an array of numbers,
+/// interpolating linearly between the closest elements.
+///
+/// If an array of percentiles is given, an array of results is returned.
+pub fn percentile(p: Value, array: Value) -> eval::Result {
+    let sorted: Vec<FloatRepr> = try!(sorted_numbers("percentile", array))
+        .into_iter().map(|(x, _)| x).collect();
+    if sorted.is_empty() {
+        return Ok(Value::Empty);
+    }
+
+    match p {
+        Value::Array(ps) => {
+            let mut result = Vec::with_capacity(ps.len());
+            for p in ps {
+                let p = try!(percent("percentile", p));
+                result.push(Value::Float(percentile_of(&sorted, p)));
+            }
+            Ok(Value::Array(result))
+        },
+        p => {
+            let p = try!(percent("percentile", p));
+            Ok(Value::Float(percentile_of(&sorted, p)))
+        },
+    }
+}
+
+/// Compute the quantiles which divide an array of numbers into n equally sized groups,
+/// e.g. the three quartiles for n = 4.
+pub fn quantiles(n: Value, array: Value) -> eval::Result {
+    let n = match n {
+        Value::Integer(n) if n > 0 => n,
+        n => return Err(Error::new(&format!(
+            "quantiles() requires a positive number of groups, got {:?}", n
+        ))),
+    };
+    if n > MAX_BINS {
+        return Err(Error::new(&format!(
+            "quantiles() supports at most {} groups, got {}", MAX_BINS, n
+        )));
+    }
+    let sorted: Vec<FloatRepr> = try!(sorted_numbers("quantiles", array))
+        .into_iter().map(|(x, _)| x).collect();
+    if sorted.is_empty() {
+        return Ok(Value::Empty);
+    }
+    Ok(Value::Array((1..n)
+        .map(|i| percentile_of(&sorted, 100.0 * i as FloatRepr / n as FloatRepr))
+        .map(Value::Float)
+        .collect()))
+}
+
+
+/// Compute the (population) variance of an array of numbers.
+pub fn variance(array: Value) -> eval::Result {
+    let xs = try!(numbers("variance", array));
+    if xs.is_empty() {
+        return Ok(Value::Empty);
+    }
+    Ok(Value::Float(variance_of(&xs)))
+}
+
+/// Compute the (population) standard deviation of an array of numbers.
+pub fn stddev(array: Value) -> eval::Result {
+    let xs = try!(numbers("stddev", array));
+    if xs.is_empty() {
+        return Ok(Value::Empty);
+    }
+    Ok(Value::Float(variance_of(&xs).sqrt()))
+}
+
+/// Compute the standard scores of an array of numbers,
+/// i.e. how many standard deviations each of them is away from the mean.
+///
+/// The numbers must not all be equal, as their standard deviation would then be zero.
+pub fn zscore(array: Value) -> eval::Result {
+    let xs = try!(numbers("zscore", array));
+    if xs.is_empty() {
+        return Ok(Value::Array(vec![]));
+    }
+    let mean = mean_of(&xs);
+    let stddev = variance_of(&xs).sqrt();
+    if stddev == 0.0 {
+        return Err(Error::new("zscore() is undefined when the standard deviation is zero"));
+    }
+    Ok(Value::Array(xs.into_iter().map(|x| Value::Float((x - mean) / stddev)).collect()))
+}
+
+/// Compute the Pearson correlation coefficient of two arrays of numbers.
+///
+/// Neither of the arrays may consist of all equal numbers,
+/// as the coefficient is undefined for a zero standard deviation.
+pub fn correlation(left: Value, right: Value) -> eval::Result {
+    let xs = try!(numbers("correlation", left));
+    let ys = try!(numbers("correlation", right));
+    if xs.len() != ys.len() {
+        return Err(Error::new(&format!(
+            "correlation() requires arrays of equal length, got {} and {}", xs.len(), ys.len()
+        )));
+    }
+    if xs.is_empty() {
+        return Ok(Value::Empty);
+    }
+
+    let (mean_x, mean_y) = (mean_of(&xs), mean_of(&ys));
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.into_iter().zip(ys) {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x) * (x - mean_x);
+        var_y += (y - mean_y) * (y - mean_y);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return Err(Error::new(
+            "correlation() is undefined when the standard deviation is zero"));
+    }
+    Ok(Value::Float(cov / (var_x * var_y).sqrt()))
+}
+
+
+/// Compute a histogram of an array of numbers.
+///
+/// The bins are given either as their number (which divides the range between
+/// the minimum and maximum into equal parts), or as an array of bin edges.
+/// Every bin includes its lower edge, but only the last one includes its upper edge;
+/// numbers outside of all bins are not counted.
+///
+/// Returns an array of objects with the "min", "max" and "count" of every bin,
+/// which can be passed to table() for display.
+pub fn histogram(bins: Value, array: Value) -> eval::Result {
+    let xs = try!(numbers("histogram", array));
+    let edges: Vec<FloatRepr> = match bins {
+        Value::Integer(n) if n > 0 => {
+            if n > MAX_BINS {
+                return Err(Error::new(&format!(
+                    "histogram() supports at most {} bins, got {}", MAX_BINS, n
+                )));
+            }
+            if xs.is_empty() {
+                return Ok(Value::Array(vec![]));
+            }
+            let min = xs.iter().cloned().fold(f64::INFINITY as FloatRepr, FloatRepr::min);
+            let max = xs.iter().cloned().fold(f64::NEG_INFINITY as FloatRepr, FloatRepr::max);
+            let step = (max - min) / n as FloatRepr;
+            (0..n + 1).map(|i| if i == n { max } else { min + step * i as FloatRepr }).collect()
+        },
+        Value::Array(edges) => {
+            let edges = try!(numbers("histogram", Value::Array(edges)));
+            if edges.len() < 2 || edges.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(Error::new(
+                    "histogram() requires at least two bin edges in increasing order"));
+            }
+            edges
+        },
+        bins => return Err(Error::new(&format!(
+            "histogram() requires a positive number of bins or an array of edges, got {:?}",
+            bins
+        ))),
+    };
+
+    let bin_count = edges.len() - 1;
+    let mut counts = vec![0; bin_count];
+    for x in xs {
+        let last = edges[bin_count];
+        if x == last {
+            counts[bin_count - 1] += 1;
+        } else if let Some(i) = edges.windows(2).position(|w| w[0] <= x && x < w[1]) {
+            counts[i] += 1;
+        }
+    }
+
+    Ok(Value::Array(edges.windows(2).zip(counts).map(|(w, count)| {
+        let mut bin = ObjectRepr::new();
+        bin.insert("min".to_owned(), Value::Float(w[0]));
+        bin.insert("max".to_owned(), Value::Float(w[1]));
+        bin.insert("count".to_owned(), Value::Integer(count as IntegerRepr));
+        Value::Object(bin)
+    }).collect()))
+}
+
+/// Maximum number of histogram bins (or quantile groups) that can be requested.
+/// This guards against accidentally allocating an enormous result.
+const MAX_BINS: IntegerRepr = 1 << 16;
+
+
+// Utility functions
+
+/// Extract the numbers from an array value, converting them to floats.
+fn numbers(name: &str, array: Value) -> Result<Vec<FloatRepr>, Error> {
+    match array {
+        Value::Array(a) => numbers_of(name, &a),
+        v => Err(Error::new(&format!(
+            "{}() requires an array of numbers, got {}", name, v.typename()
+        ))),
+    }
+}
+
+fn numbers_of(name: &str, array: &ArrayRepr) -> Result<Vec<FloatRepr>, Error> {
+    array.iter().map(|v| match *v {
+        Value::Integer(i) => Ok(i as FloatRepr),
+        Value::Float(f) => Ok(f),
+        _ => Err(Error::new(&format!(
+            "{}() requires an array of numbers, got an element of type {}", name, v.typename()
+        ))),
+    }).collect()
+}
+
+/// Extract the numbers from an array value and sort them,
+/// retaining the original values alongside their float counterparts.
+fn sorted_numbers(name: &str, array: Value) -> Result<Vec<(FloatRepr, Value)>, Error> {
+    let array = match array {
+        Value::Array(a) => a,
+        v => return Err(Error::new(&format!(
+            "{}() requires an array of numbers, got {}", name, v.typename()
+        ))),
+    };
+    let xs = try!(numbers_of(name, &array));
+    let mut result: Vec<_> = xs.into_iter().zip(array).collect();
+    result.sort_by(|&(a, _), &(b, _)| a.partial_cmp(&b).unwrap_or(Ordering::Equal));
+    Ok(result)
+}
+
+/// Interpret a value as percentage between 0 and 100.
+fn percent(name: &str, p: Value) -> Result<FloatRepr, Error> {
+    let p = match p {
+        Value::Integer(i) => i as FloatRepr,
+        Value::Float(f) => f,
+        p => return Err(Error::new(&format!(
+            "{}() requires a number between 0 and 100, got {}", name, p.typename()
+        ))),
+    };
+    if 0.0 <= p && p <= 100.0 {
+        Ok(p)
+    } else {
+        Err(Error::new(&format!("{}() requires a number between 0 and 100, got {}", name, p)))
+    }
+}
+
+fn mean_of(xs: &[FloatRepr]) -> FloatRepr {
+    xs.iter().fold(0.0, |acc, x| acc + x) / xs.len() as FloatRepr
+}
+
+fn variance_of(xs: &[FloatRepr]) -> FloatRepr {
+    let mean = mean_of(xs);
+    xs.iter().map(|x| (x - mean) * (x - mean)).fold(0.0, |acc, x| acc + x) / xs.len() as FloatRepr
+}
+
+/// Compute the p-th percentile of a non-empty, sorted array of numbers.
+fn percentile_of(sorted: &[FloatRepr], p: FloatRepr) -> FloatRepr {
+    let rank = p / 100.0 * (sorted.len() - 1) as FloatRepr;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as FloatRepr)
+}
@@ -0,0 +1,150 @@
+//! Functions for changing the letter case of strings.
+//!
+//! All of them accept either a single string or an array of strings,
+//! in which case the conversion is applied to every element.
+
+use std::mem;
+
+use eval::{self, Value};
+
+
+/// Convert all letters of the string to uppercase.
+pub fn upper(value: Value) -> eval::Result {
+    map_strings("upper", value, &|s| s.to_uppercase())
+}
+
+/// Convert all letters of the string to lowercase.
+pub fn lower(value: Value) -> eval::Result {
+    map_strings("lower", value, &|s| s.to_lowercase())
+}
+
+/// Convert the first letter of every word in the string to uppercase,
+/// and the remaining ones to lowercase.
+pub fn title(value: Value) -> eval::Result {
+    map_strings("title", value, &|s| {
+        let mut result = String::with_capacity(s.len());
+        let mut in_word = false;
+        for c in s.chars() {
+            if in_word {
+                result.extend(c.to_lowercase());
+            } else {
+                result.extend(c.to_uppercase());
+            }
+            // Apostrophes don't start a new word, as in "don't".
+            in_word = c.is_alphanumeric() || (in_word && c == '\'');
+        }
+        result
+    })
+}
+
+/// Convert the first character of the string to uppercase,
+/// and all the other ones to lowercase.
+pub fn capitalize(value: Value) -> eval::Result {
+    map_strings("capitalize", value, &capitalize_str)
+}
+
+/// Fold the case of the string, making it suitable for case-insensitive comparisons.
+///
+/// This is more thorough than lower(), as it also unifies characters like "ß" and "ss"
+/// which only differ in their uppercase forms.
+pub fn casefold(value: Value) -> eval::Result {
+    map_strings("casefold", value, &|s| s.to_uppercase().to_lowercase())
+}
+
+
+/// Convert the string to snake_case.
+pub fn snake(value: Value) -> eval::Result {
+    map_strings("snake", value, &|s| {
+        let words: Vec<_> = split_words(s).iter().map(|w| w.to_lowercase()).collect();
+        words.join("_")
+    })
+}
+
+/// Convert the string to kebab-case.
+pub fn kebab(value: Value) -> eval::Result {
+    map_strings("kebab", value, &|s| {
+        let words: Vec<_> = split_words(s).iter().map(|w| w.to_lowercase()).collect();
+        words.join("-")
+    })
+}
+
+/// Convert the string to CONSTANT_CASE.
+pub fn constant(value: Value) -> eval::Result {
+    map_strings("constant", value, &|s| {
+        let words: Vec<_> = split_words(s).iter().map(|w| w.to_uppercase()).collect();
+        words.join("_")
+    })
+}
+
+/// Convert the string to camelCase.
+pub fn camel(value: Value) -> eval::Result {
+    map_strings("camel", value, &|s| {
+        let mut words = split_words(s).into_iter();
+        let first = words.next().map(|w| w.to_lowercase()).unwrap_or_else(String::new);
+        words.fold(first, |result, w| result + &capitalize_str(&w))
+    })
+}
+
+/// Convert the string to PascalCase.
+pub fn pascal(value: Value) -> eval::Result {
+    map_strings("pascal", value, &|s| {
+        split_words(s).iter().map(|w| capitalize_str(w)).collect()
+    })
+}
+
+
+// Utility functions
+
+/// Apply a string conversion to given value,
+/// which can be either a string or an array of them.
+fn map_strings(name: &str, value: Value, func: &Fn(&str) -> String) -> eval::Result {
+    match value {
+        Value::String(ref s) => Ok(Value::String(func(s))),
+        Value::Array(a) => {
+            let mut result = Vec::with_capacity(a.len());
+            for v in a {
+                result.push(try!(map_strings(name, v, func)));
+            }
+            Ok(Value::Array(result))
+        },
+        value => mismatch!(name; ("string") | ("array") => (value)),
+    }
+}
+
+fn capitalize_str(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+/// Split the string into words which comprise an identifier in any of the common naming styles.
+///
+/// Besides non-alphanumeric characters, words are separated by changes of letter case,
+/// so that e.g. "parseHTTPRequest" consists of "parse", "HTTP", and "Request".
+fn split_words(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(mem::replace(&mut word, String::new()));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).map_or(false, |n| n.is_lowercase());
+            if !prev.is_uppercase() || next_is_lower {
+                words.push(mem::replace(&mut word, String::new()));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
@@ -1,5 +1,7 @@
 //! API for blowing strings into fragments and putting them back together.
 
+use std::mem;
+
 use regex::Regex;
 
 use eval::{self, Error, Value};
@@ -42,12 +44,11 @@ pub fn join(delim: Value, array: Value) -> eval::Result {
 }
 
 
-/// Split a string by given string delimiter.
+/// Split a string by given string or regex delimiter.
 /// Returns an array of strings.
-// TODO(xion): introduce optional third parameter, maxsplit
 pub fn split(delim: Value, string: Value) -> eval::Result {
     eval2!((delim: &String, string: &String) -> Array {
-        string.split(delim).map(StringRepr::from).map(Value::String).collect()
+        string.split(delim as &str).map(StringRepr::from).map(Value::String).collect()
     });
     eval2!((delim: &Regex, string: &String) -> Array {
         do_regex_split(delim, string)
@@ -55,6 +56,92 @@ pub fn split(delim: Value, string: Value) -> eval::Result {
     mismatch!("split"; ("string", "string") | ("regex", "string") => (delim, string))
 }
 
+/// Split a string by given string or regex delimiter, at most given number of times.
+///
+/// The remainder of the string after the last split becomes the final element
+/// of the resulting array, so it has at most n + 1 elements.
+pub fn splitn(n: Value, delim: Value, string: Value) -> eval::Result {
+    let limit = try!(split_limit("splitn", n));
+    eval2!((delim: &String, string: &String) -> Array {
+        string.splitn(limit, delim as &str).map(StringRepr::from).map(Value::String).collect()
+    });
+    eval2!((delim: &Regex, string: &String) -> Array {
+        delim.splitn(string, limit).map(StringRepr::from).map(Value::String).collect()
+    });
+    mismatch!("splitn"; ("string", "string") | ("regex", "string") => (delim, string))
+}
+
+/// Split a string by given string or regex delimiter at most given number of times,
+/// starting from the end.
+///
+/// Unlike with splitn(), it's the remainder of the string *before* the first split
+/// that becomes the first element of the array.
+pub fn rsplitn(n: Value, delim: Value, string: Value) -> eval::Result {
+    let limit = try!(split_limit("rsplitn", n));
+    eval2!((delim: &String, string: &String) -> Array {{
+        let mut parts: ArrayRepr = string.rsplitn(limit, delim as &str)
+            .map(StringRepr::from).map(Value::String).collect();
+        parts.reverse();
+        parts
+    }});
+    eval2!((delim: &Regex, string: &String) -> Array {
+        do_regex_rsplitn(delim, string, limit)
+    });
+    mismatch!("rsplitn"; ("string", "string") | ("regex", "string") => (delim, string))
+}
+
+/// Split a string at the first occurrence of given string or regex separator.
+///
+/// Returns a three-element array: the part before the separator, the separator itself,
+/// and the part after it. If the separator is not found, the array consists of
+/// the whole string and two empty ones.
+pub fn partition(sep: Value, string: Value) -> eval::Result {
+    eval2!((sep: &String, string: &String) -> Array {
+        match string.find(sep as &str) {
+            Some(index) => partition_at(string, index, index + sep.len()),
+            None => partition_at(string, string.len(), string.len()),
+        }
+    });
+    eval2!((sep: &Regex, string: &String) -> Array {
+        match sep.find(string) {
+            Some((start, end)) => partition_at(string, start, end),
+            None => partition_at(string, string.len(), string.len()),
+        }
+    });
+    mismatch!("partition"; ("string", "string") | ("regex", "string") => (sep, string))
+}
+
+/// Split a string at the last occurrence of given string or regex separator.
+///
+/// Returns a three-element array like partition(), except that when the separator
+/// is not found, it's the last element that contains the whole string.
+pub fn rpartition(sep: Value, string: Value) -> eval::Result {
+    eval2!((sep: &String, string: &String) -> Array {
+        match string.rfind(sep as &str) {
+            Some(index) => partition_at(string, index, index + sep.len()),
+            None => partition_at(string, 0, 0),
+        }
+    });
+    eval2!((sep: &Regex, string: &String) -> Array {
+        match sep.find_iter(string).last() {
+            Some((start, end)) => partition_at(string, start, end),
+            None => partition_at(string, 0, 0),
+        }
+    });
+    mismatch!("rpartition"; ("string", "string") | ("regex", "string") => (sep, string))
+}
+
+/// Split a string into words the way a POSIX shell would,
+/// respecting single and double quotes as well as backslash escapes.
+pub fn shlex(string: Value) -> eval::Result {
+    if let Value::String(ref s) = string {
+        return shell_words(s)
+            .map(|words| Value::Array(words.into_iter().map(Value::String).collect()))
+            .map_err(|e| Error::new(&format!("shlex() failed to split the string: {}", e)));
+    }
+    mismatch!("shlex"; ("string") => (string))
+}
+
 /// Split a string into array of words.
 pub fn words(string: Value) -> eval::Result {
     eval1!((string: &String) -> Array { do_regex_split(&WORD_SEP, string) });
@@ -67,6 +154,43 @@ pub fn lines(string: Value) -> eval::Result {
     mismatch!("lines"; ("string") => (string))
 }
 
+/// Split a string into array of lines, recognizing "\n", "\r\n" and "\r" as line endings.
+///
+/// Unlike lines(), a line ending at the very end of the string doesn't result
+/// in an additional empty line. If the optional flag is true, the line endings
+/// are kept at the end of each line.
+pub fn splitlines(string: Value, keepends: Option<Value>) -> eval::Result {
+    let keepends = match keepends {
+        None => false,
+        Some(Value::Boolean(b)) => b,
+        Some(k) => return Err(Error::new(&format!(
+            "splitlines() expects a boolean flag, got {}", k.typename()
+        ))),
+    };
+    eval1!((string: &String) -> Array {{
+        let mut result = ArrayRepr::new();
+        let mut start = 0;
+        let bytes = string.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let ending = match bytes[i] {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => 2,
+                b'\r' | b'\n' => 1,
+                _ => { i += 1; continue; },
+            };
+            let end = if keepends { i + ending } else { i };
+            result.push(Value::String(string[start..end].to_owned()));
+            i += ending;
+            start = i;
+        }
+        if start < string.len() {
+            result.push(Value::String(string[start..].to_owned()));
+        }
+        result
+    }});
+    mismatch!("splitlines"; ("string") => (string))
+}
+
 
 // Utility functions
 
@@ -74,3 +198,89 @@ pub fn lines(string: Value) -> eval::Result {
 fn do_regex_split(delim: &Regex, string: &str) -> ArrayRepr {
     delim.split(string).map(StringRepr::from).map(Value::String).collect()
 }
+
+/// Split the string by regex delimiter into at most `limit` parts,
+/// using only the last of its matches if there are more.
+fn do_regex_rsplitn(delim: &Regex, string: &str, limit: usize) -> ArrayRepr {
+    let matches: Vec<_> = delim.find_iter(string).collect();
+    let skipped = matches.len().saturating_sub(limit - 1);
+
+    let mut parts = ArrayRepr::with_capacity(matches.len() - skipped + 1);
+    let mut last = 0;
+    for &(start, end) in &matches[skipped..] {
+        parts.push(Value::String(StringRepr::from(&string[last..start])));
+        last = end;
+    }
+    parts.push(Value::String(StringRepr::from(&string[last..])));
+    parts
+}
+
+/// Convert the maximum number of splits into a limit on the number of resulting parts.
+fn split_limit(name: &str, n: Value) -> Result<usize, Error> {
+    match n {
+        Value::Integer(n) if n >= 0 => Ok((n as usize).saturating_add(1)),
+        n => Err(Error::new(&format!(
+            "{}() expects a non-negative maximum number of splits, got {:?}", name, n
+        ))),
+    }
+}
+
+/// Split the string into the part before given range, the range itself, and the part after it.
+fn partition_at(string: &str, start: usize, end: usize) -> ArrayRepr {
+    vec![&string[..start], &string[start..end], &string[end..]].into_iter()
+        .map(StringRepr::from).map(Value::String)
+        .collect()
+}
+
+/// Split the string into words according to the POSIX shell quoting rules.
+fn shell_words(s: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;  // needed to tell empty quoted words ("") from no word
+
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated single quote".to_owned()),
+                    }
+                }
+            },
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ '"') | Some(c @ '\\') | Some(c @ '$') | Some(c @ '`') => word.push(c),
+                            Some('\n') => {},
+                            Some(c) => { word.push('\\'); word.push(c); },
+                            None => return Err("unterminated double quote".to_owned()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated double quote".to_owned()),
+                    }
+                }
+            },
+            '\\' => match chars.next() {
+                Some('\n') => {},
+                Some(c) => { in_word = true; word.push(c); },
+                None => return Err("dangling backslash at the end".to_owned()),
+            },
+            c if c.is_whitespace() => if in_word {
+                words.push(mem::replace(&mut word, String::new()));
+                in_word = false;
+            },
+            c => { in_word = true; word.push(c); },
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
@@ -0,0 +1,321 @@
+//! Functions for laying out text: padding, wrapping, indentation and tables.
+//!
+//! Widths are measured in terminal columns rather than characters,
+//! so that e.g. CJK ideographs count as two columns and combining marks as none.
+
+use std::cmp::max;
+use std::iter::repeat;
+use std::mem;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use eval::{self, Error, Value};
+use eval::api::conv::str_;
+use eval::value::{ArrayRepr, ObjectRepr, StringRepr};
+
+
+/// Pad the string on the left with spaces, so that it's at least given width.
+/// This aligns the text to the right.
+pub fn lpad(width: Value, string: Value) -> eval::Result {
+    eval2!((width: Integer, string: &String) -> String where (width >= 0) {
+        pad(string, width as usize, Align::Right)
+    });
+    mismatch!("lpad"; ("integer", "string") => (width, string))
+}
+
+/// Pad the string on the right with spaces, so that it's at least given width.
+/// This aligns the text to the left.
+pub fn rpad(width: Value, string: Value) -> eval::Result {
+    eval2!((width: Integer, string: &String) -> String where (width >= 0) {
+        pad(string, width as usize, Align::Left)
+    });
+    mismatch!("rpad"; ("integer", "string") => (width, string))
+}
+
+/// Pad the string on both sides with spaces, so that it's centered within given width.
+pub fn center(width: Value, string: Value) -> eval::Result {
+    eval2!((width: Integer, string: &String) -> String where (width >= 0) {
+        pad(string, width as usize, Align::Center)
+    });
+    mismatch!("center"; ("integer", "string") => (width, string))
+}
+
+
+/// Wrap the text so that its lines are no wider than given width, if possible.
+///
+/// Lines are only broken at whitespace, so words that are wider than the limit
+/// end up on lines of their own. Existing line breaks are preserved.
+pub fn wrap(width: Value, string: Value) -> eval::Result {
+    eval2!((width: Integer, string: &String) -> String where (width >= 0) {
+        string.lines()
+            .flat_map(|line| wrap_line(line, width as usize))
+            .collect::<Vec<_>>().join("\n")
+    });
+    mismatch!("wrap"; ("integer", "string") => (width, string))
+}
+
+/// Truncate the string to given width, replacing its end with an ellipsis
+/// (e.g. "...") if anything had to be cut off.
+pub fn truncate(width: Value, ellipsis: Value, string: Value) -> eval::Result {
+    if let (&Value::Integer(w), &Value::String(ref e), &Value::String(ref s)) =
+            (&width, &ellipsis, &string) {
+        let width = if w >= 0 { w as usize } else {
+            return Err(Error::new(&format!("truncate() expects a non-negative width, got {}", w)));
+        };
+        if s.width() <= width {
+            return Ok(Value::String(s.clone()));
+        }
+        let available = match width.checked_sub(e.width()) {
+            Some(w) => w,
+            None => return Err(Error::new(&format!(
+                "truncate() ellipsis is wider than {} column(s)", width
+            ))),
+        };
+
+        let mut result = String::new();
+        let mut result_width = 0;
+        for g in s.graphemes(/* extended grapheme clusters */ true) {
+            result_width += g.width();
+            if result_width > available {
+                break;
+            }
+            result.push_str(g);
+        }
+        result.push_str(e);
+        return Ok(Value::String(result));
+    }
+    mismatch!("truncate"; ("integer", "string", "string") => (width, ellipsis, string))
+}
+
+
+/// Indent every non-blank line of the text with given prefix,
+/// or given number of spaces.
+pub fn indent(prefix: Value, string: Value) -> eval::Result {
+    let prefix = match prefix {
+        Value::Integer(n) if n >= 0 => repeat(' ').take(n as usize).collect(),
+        Value::String(p) => p,
+        other => return mismatch!("indent"; ("integer", "string") | ("string", "string")
+                                          => (other, string)),
+    };
+    eval1!(string : &String {
+        string.split('\n')
+            .map(|line| if line.trim().is_empty() { line.to_owned() }
+                        else { format!("{}{}", prefix, line) })
+            .collect::<Vec<_>>().join("\n")
+    });
+    Err(Error::new(&format!("indent() expects a string, got {}", string.typename())))
+}
+
+/// Remove any whitespace that's common to the beginning of every non-blank line of the text.
+/// Lines consisting only of whitespace are emptied.
+pub fn dedent(string: Value) -> eval::Result {
+    eval1!(string : &String {{
+        let margin = string.split('\n')
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| &line[..line.len() - line.trim_left().len()])
+            .fold(None, |margin: Option<&str>, ws| Some(match margin {
+                Some(m) => common_prefix(m, ws),
+                None => ws,
+            }))
+            .unwrap_or("");
+        string.split('\n')
+            .map(|line| if line.trim().is_empty() { "" } else { &line[margin.len()..] })
+            .collect::<Vec<_>>().join("\n")
+    }});
+    mismatch!("dedent"; ("string") => (string))
+}
+
+
+/// Render an array of rows as a table with aligned columns.
+///
+/// Rows can be either arrays of cells, or objects whose keys become the table's header.
+/// Numeric columns are aligned to the right.
+///
+/// The format is either "plain" (the default) or "markdown". In the latter case,
+/// the first array row is used as header if there is no other one.
+pub fn table(rows: Value, format: Option<Value>) -> eval::Result {
+    let markdown = match format {
+        None => false,
+        Some(Value::String(ref f)) if f == "plain" => false,
+        Some(Value::String(ref f)) if f == "markdown" || f == "md" => true,
+        Some(f) => return Err(Error::new(&format!(
+            "table() expects \"plain\" or \"markdown\" format, got {:?}", f
+        ))),
+    };
+    let rows = match rows {
+        Value::Array(a) => a,
+        rows => return mismatch!("table"; ("array") => (rows)),
+    };
+
+    let (header, mut body) = try!(table_cells(rows));
+    let header = match header {
+        Some(h) => h,
+        None if markdown && !body.is_empty() =>
+            body.remove(0).into_iter().map(|c| c.text).collect(),
+        None => vec![],
+    };
+
+    if markdown {
+        for cell in body.iter_mut().flat_map(|row| row.iter_mut()) {
+            cell.text = escape_markdown(&cell.text);
+        }
+    }
+    let header: Vec<String> = if markdown { header.iter().map(|h| escape_markdown(h)).collect() }
+                              else { header };
+
+    // compute the properties of every column: its width, and whether it's numeric
+    let column_count = body.iter().map(Vec::len).chain(Some(header.len())).max().unwrap_or(0);
+    let mut widths = vec![if markdown { 3 } else { 0 }; column_count];
+    let mut numeric = vec![false; column_count];
+    for (i, h) in header.iter().enumerate() {
+        widths[i] = max(widths[i], h.width());
+    }
+    for i in 0..column_count {
+        let cells: Vec<&Cell> = body.iter().filter_map(|row| row.get(i))
+            .filter(|c| !c.text.is_empty()).collect();
+        numeric[i] = !cells.is_empty() && cells.iter().all(|c| c.numeric);
+        widths[i] = cells.iter().map(|c| c.text.width()).chain(Some(widths[i])).max().unwrap();
+    }
+
+    let render_row = |texts: Vec<&str>| {
+        let cells: Vec<String> = (0..column_count).map(|i| {
+            let text = texts.get(i).cloned().unwrap_or("");
+            pad(text, widths[i], if numeric[i] { Align::Right } else { Align::Left })
+        }).collect();
+        if markdown { format!("| {} |", cells.join(" | ")) }
+        else { cells.join(TABLE_COLUMN_SEP).trim_right().to_owned() }
+    };
+
+    let mut lines = Vec::with_capacity(body.len() + 2);
+    if !header.is_empty() {
+        lines.push(render_row(header.iter().map(|h| h as &str).collect()));
+        let rules: Vec<String> = (0..column_count).map(|i| {
+            let dashes: String = repeat('-').take(widths[i]).collect();
+            match (markdown, numeric[i]) {
+                (true, true) => format!("{}:", &dashes[1..]),
+                _ => dashes,
+            }
+        }).collect();
+        lines.push(if markdown { format!("| {} |", rules.join(" | ")) }
+                   else { rules.join(TABLE_COLUMN_SEP) });
+    }
+    for row in &body {
+        lines.push(render_row(row.iter().map(|c| &c.text as &str).collect()));
+    }
+    Ok(Value::String(lines.join("\n")))
+}
+
+const TABLE_COLUMN_SEP: &'static str = "  ";
+
+
+// Utility functions
+
+/// How to align text when padding it.
+#[derive(Clone,Copy)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+fn pad(s: &str, width: usize, align: Align) -> String {
+    let padding = width.saturating_sub(s.width());
+    let (left, right) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    let mut result = String::with_capacity(s.len() + padding);
+    result.extend(repeat(' ').take(left));
+    result.push_str(s);
+    result.extend(repeat(' ').take(right));
+    result
+}
+
+/// Wrap a single line of text at given width.
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    for word in line.split_whitespace() {
+        let word_width = word.width();
+        if !current.is_empty() && current_width + 1 + word_width > width {
+            lines.push(mem::replace(&mut current, String::new()));
+            current_width = 0;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+            current_width += 1;
+        }
+        current.push_str(word);
+        current_width += word_width;
+    }
+    lines.push(current);
+    lines
+}
+
+/// Longest common prefix of two strings.
+fn common_prefix<'s>(a: &'s str, b: &str) -> &'s str {
+    let len = a.char_indices().zip(b.chars())
+        .take_while(|&((_, x), y)| x == y)
+        .last().map(|((i, c), _)| i + c.len_utf8())
+        .unwrap_or(0);
+    &a[..len]
+}
+
+fn escape_markdown(s: &str) -> String {
+    s.replace("|", "\\|")
+}
+
+
+/// Single cell of a table.
+struct Cell {
+    text: StringRepr,
+    numeric: bool,
+}
+
+impl Cell {
+    fn new(value: Value) -> Result<Cell, Error> {
+        let numeric = value.is_number();
+        let text = match value {
+            Value::Empty => String::new(),
+            v => try!(str_(v)).unwrap_string(),
+        };
+        Ok(Cell{text: text, numeric: numeric})
+    }
+}
+
+/// Convert the table rows into cells, as well as the header (if the rows are objects).
+fn table_cells(rows: ArrayRepr) -> Result<(Option<Vec<String>>, Vec<Vec<Cell>>), Error> {
+    if rows.iter().all(Value::is_array) {
+        let mut body = Vec::with_capacity(rows.len());
+        for row in rows {
+            let cells: Result<Vec<Cell>, Error> =
+                row.unwrap_array().into_iter().map(Cell::new).collect();
+            body.push(try!(cells));
+        }
+        return Ok((None, body));
+    }
+
+    if rows.iter().all(Value::is_object) {
+        let objects: Vec<ObjectRepr> = rows.into_iter().map(Value::unwrap_object).collect();
+        let mut columns: Vec<String> = Vec::new();
+        for key in objects.iter().flat_map(|o| o.keys()) {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+        let mut body = Vec::with_capacity(objects.len());
+        for mut object in objects {
+            let mut row = Vec::with_capacity(columns.len());
+            for column in &columns {
+                row.push(try!(Cell::new(object.remove(column).unwrap_or(Value::Empty))));
+            }
+            body.push(row);
+        }
+        return Ok((Some(columns), body));
+    }
+
+    Err(Error::new("table() expects an array of arrays or an array of objects"))
+}
@@ -0,0 +1,123 @@
+//! Regex matching functions.
+
+use std::iter::Peekable;
+
+use regex::{Captures, Regex};
+
+use eval::{self, Value};
+use eval::value::{ObjectRepr, StringRepr};
+
+
+/// Match a regex against given string, returning the match object,
+/// or nil if the string doesn't match.
+///
+/// Match object contains the values of all capture groups,
+/// keyed by both their index ("0" being the whole match) and, if the group is named, its name.
+/// Groups that didn't participate in the match have nil values.
+pub fn match_(regex: Value, string: Value) -> eval::Result {
+    if let (&Value::Regex(ref r), &Value::String(ref s)) = (&regex, &string) {
+        return Ok(r.captures(s).map(|c| match_object(r, &c)).unwrap_or(Value::Empty));
+    }
+    mismatch!("match"; ("regex", "string") => (regex, string))
+}
+
+/// Find all non-overlapping matches of a regex within given string.
+/// Returns an array of match objects (as returned by match()).
+pub fn matchall(regex: Value, string: Value) -> eval::Result {
+    eval2!((regex: &Regex, string: &String) -> Array {
+        regex.captures_iter(string).map(|c| match_object(regex, &c)).collect()
+    });
+    mismatch!("matchall"; ("regex", "string") => (regex, string))
+}
+
+/// Match a regex against given string, returning the array of values
+/// of its capture groups (excluding the whole match), or nil if the string doesn't match.
+pub fn captures(regex: Value, string: Value) -> eval::Result {
+    if let (&Value::Regex(ref r), &Value::String(ref s)) = (&regex, &string) {
+        return Ok(r.captures(s).map(|c| {
+            Value::Array(c.iter().skip(1).map(capture_value).collect())
+        }).unwrap_or(Value::Empty));
+    }
+    mismatch!("captures"; ("regex", "string") => (regex, string))
+}
+
+
+/// Expand the references to capture groups within a replacement string.
+///
+/// Groups can be referred to by index (`\1`, `$1`, or `${1}`)
+/// or by name (`$name` or `${name}`). References to nonexistent groups
+/// expand to empty string. Use `$$` for a literal dollar sign and `\\` for a backslash.
+pub fn expand_captures(caps: &Captures, replacement: &str) -> StringRepr {
+    let mut result = StringRepr::with_capacity(replacement.len());
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek().cloned()) {
+            ('\\', Some('\\')) | ('$', Some('$')) => {
+                result.push(c);
+                chars.next();
+            },
+            ('\\', Some(d)) if d.is_digit(10) => {
+                let index = take_while(&mut chars, |c| c.is_digit(10));
+                result.push_str(capture_ref(caps, &index));
+            },
+            ('$', Some('{')) => {
+                chars.next();
+                let name = take_while(&mut chars, |c| c != '}');
+                if chars.next().is_none() {
+                    // unterminated reference is left as it was
+                    result.push_str("${");
+                    result.push_str(&name);
+                } else {
+                    result.push_str(capture_ref(caps, &name));
+                }
+            },
+            ('$', Some(n)) if n.is_alphanumeric() || n == '_' => {
+                let name = take_while(&mut chars, |c| c.is_alphanumeric() || c == '_');
+                result.push_str(capture_ref(caps, &name));
+            },
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+
+// Utility functions
+
+/// Create the match object from regex captures.
+fn match_object(regex: &Regex, caps: &Captures) -> Value {
+    let mut result = ObjectRepr::new();
+    for (i, cap) in caps.iter().enumerate() {
+        result.insert(i.to_string(), capture_value(cap));
+    }
+    for name in regex.capture_names().filter_map(|n| n) {
+        result.insert(name.to_owned(), capture_value(caps.name(name)));
+    }
+    Value::Object(result)
+}
+
+#[inline]
+fn capture_value(cap: Option<&str>) -> Value {
+    cap.map(StringRepr::from).map(Value::String).unwrap_or(Value::Empty)
+}
+
+/// Resolve a reference to capture group, given either as its index or name.
+fn capture_ref<'t>(caps: &Captures<'t>, reference: &str) -> &'t str {
+    let cap = match reference.parse::<usize>() {
+        Ok(i) => caps.at(i),
+        Err(_) => caps.name(reference),
+    };
+    cap.unwrap_or("")
+}
+
+fn take_while<I, P>(chars: &mut Peekable<I>, pred: P) -> String
+    where I: Iterator<Item=char>, P: Fn(char) -> bool
+{
+    let mut result = String::new();
+    while let Some(&c) = chars.peek() {
+        if !pred(c) { break; }
+        result.push(c);
+        chars.next();
+    }
+    result
+}
@@ -1,10 +1,18 @@
 //! String API available to expressions.
 
+mod case;
 mod frag;
+mod layout;
+mod matching;
 mod subst;
+mod unicode;
 
+pub use self::case::*;
 pub use self::frag::*;
+pub use self::layout::*;
+pub use self::matching::*;
 pub use self::subst::*;
+pub use self::unicode::*;
 
 
 use std::char;
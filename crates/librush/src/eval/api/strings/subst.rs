@@ -10,6 +10,7 @@ use regex::{Captures, Regex};
 
 use eval::{self, Context, Error, Value};
 use eval::api::conv::str_;
+use super::matching::expand_captures;
 use eval::model::{Args, Invoke};
 use eval::value::StringRepr;
 
@@ -63,6 +64,7 @@ pub fn latin1(value: Value) -> eval::Result {
 ///
 /// The replacement can be either another string, or -- in case of regex needle --
 /// a function accepting the values of regex captures and returning replacement string.
+/// String replacement may refer to capture groups as `\1`, `$1`, `${1}`, `$name` or `${name}`.
 ///
 /// Returns the text after the substitutions has been made.
 pub fn sub(needle: Value, replacement: Value, haystack: Value, ctx: &Context) -> eval::Result {
@@ -90,6 +92,7 @@ pub fn sub(needle: Value, replacement: Value, haystack: Value, ctx: &Context) ->
 ///
 /// The replacement can be either another string, or -- in case of regex needle --
 /// a function accepting the values of regex captures and returning replacement string.
+/// String replacement may refer to capture groups the same way as in sub().
 ///
 /// Returns the text after the substitution has been made.
 pub fn sub1(needle: Value, replacement: Value, haystack: Value, ctx: &Context) -> eval::Result {
@@ -158,14 +161,16 @@ enum Sub {
 }
 
 /// Perform a regex-based substitution.
-/// Replacement can be either a string or a function taking capture group values.
+/// Replacement can be either a string (with possible references to capture groups)
+/// or a function taking capture group values.
 fn do_regex_sub(how: Sub,
                 needle: &Regex, replacement: &Value, haystack: &String,
                 ctx: &Context) -> eval::Result {
     if let Value::String(ref r) = *replacement {
+        let expand = |caps: &Captures| expand_captures(caps, r);
         let result = match how {
-            Sub::All => needle.replace_all(haystack, r as &str),
-            Sub::First => needle.replace(haystack, r as &str),
+            Sub::All => needle.replace_all(haystack, expand),
+            Sub::First => needle.replace(haystack, expand),
         };
         return Ok(Value::String(result));
     }
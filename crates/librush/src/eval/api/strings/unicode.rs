@@ -0,0 +1,176 @@
+//! Unicode-related string functions.
+
+use unicode_categories::UnicodeCategories;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use eval::{self, Error, Value};
+use eval::value::{IntegerRepr, StringRepr};
+
+
+/// Convert the string to Unicode Normalization Form C (canonical composition).
+pub fn nfc(value: Value) -> eval::Result {
+    eval1!(value : &String { value.nfc().collect() });
+    mismatch!("nfc"; ("string") => (value))
+}
+
+/// Convert the string to Unicode Normalization Form D (canonical decomposition).
+pub fn nfd(value: Value) -> eval::Result {
+    eval1!(value : &String { value.nfd().collect() });
+    mismatch!("nfd"; ("string") => (value))
+}
+
+/// Convert the string to Unicode Normalization Form KC (compatibility composition).
+pub fn nfkc(value: Value) -> eval::Result {
+    eval1!(value : &String { value.nfkc().collect() });
+    mismatch!("nfkc"; ("string") => (value))
+}
+
+/// Convert the string to Unicode Normalization Form KD (compatibility decomposition).
+pub fn nfkd(value: Value) -> eval::Result {
+    eval1!(value : &String { value.nfkd().collect() });
+    mismatch!("nfkd"; ("string") => (value))
+}
+
+
+/// Split the string into an array of its grapheme clusters,
+/// i.e. what the user would perceive as single characters.
+pub fn graphemes(value: Value) -> eval::Result {
+    eval1!((value: &String) -> Array {
+        text_units(value, true).into_iter()
+            .map(StringRepr::from).map(Value::String)
+            .collect()
+    });
+    mismatch!("graphemes"; ("string") => (value))
+}
+
+/// Compute the length of the string in grapheme clusters.
+pub fn glen(value: Value) -> eval::Result {
+    eval1!((value: &String) -> Integer {
+        value.graphemes(/* extended grapheme clusters */ true).count() as IntegerRepr
+    });
+    mismatch!("glen"; ("string") => (value))
+}
+
+/// Compute the number of columns that the string would occupy when displayed in a terminal.
+/// East Asian wide characters (like CJK ideographs) take two columns each.
+pub fn width(value: Value) -> eval::Result {
+    eval1!((value: &String) -> Integer { value.width() as IntegerRepr });
+    mismatch!("width"; ("string") => (value))
+}
+
+
+/// Return the two-letter abbreviation of the Unicode general category
+/// of the only character in a string, e.g. "Lu" for an uppercase letter.
+pub fn category(value: Value) -> eval::Result {
+    if let Value::String(ref s) = value {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            let abbrev = CATEGORIES.iter()
+                .find(|&&(_, pred)| pred(c))
+                .map(|&(abbrev, _)| abbrev)
+                .unwrap_or(UNASSIGNED);
+            return Ok(Value::String(abbrev.to_owned()));
+        }
+        return Err(Error::new(&format!(
+            "category() requires a single character, got {} of them", s.chars().count()
+        )));
+    }
+    mismatch!("category"; ("string") => (value))
+}
+
+const UNASSIGNED: &'static str = "Cn";
+const CATEGORIES: &'static [(&'static str, fn(char) -> bool)] = &[
+    ("Cc", char::is_other_control),
+    ("Cf", char::is_other_format),
+    ("Co", char::is_other_private_use),
+    ("Ll", char::is_letter_lowercase),
+    ("Lm", char::is_letter_modifier),
+    ("Lo", char::is_letter_other),
+    ("Lt", char::is_letter_titlecase),
+    ("Lu", char::is_letter_uppercase),
+    ("Mc", char::is_mark_spacing_combining),
+    ("Me", char::is_mark_enclosing),
+    ("Mn", char::is_mark_nonspacing),
+    ("Nd", char::is_number_decimal_digit),
+    ("Nl", char::is_number_letter),
+    ("No", char::is_number_other),
+    ("Pc", char::is_punctuation_connector),
+    ("Pd", char::is_punctuation_dash),
+    ("Pe", char::is_punctuation_close),
+    ("Pf", char::is_punctuation_final_quote),
+    ("Pi", char::is_punctuation_initial_quote),
+    ("Po", char::is_punctuation_other),
+    ("Ps", char::is_punctuation_open),
+    ("Sc", char::is_symbol_currency),
+    ("Sk", char::is_symbol_modifier),
+    ("Sm", char::is_symbol_math),
+    ("So", char::is_symbol_other),
+    ("Zl", char::is_separator_line),
+    ("Zp", char::is_separator_paragraph),
+    ("Zs", char::is_separator_space),
+];
+
+
+/// Check whether the string is non-empty and consists only of letters.
+pub fn isalpha(value: Value) -> eval::Result {
+    check_chars("isalpha", value, char::is_alphabetic)
+}
+
+/// Check whether the string is non-empty and consists only of letters and/or digits.
+pub fn isalnum(value: Value) -> eval::Result {
+    check_chars("isalnum", value, char::is_alphanumeric)
+}
+
+/// Check whether the string is non-empty and consists only of decimal digits
+/// (in any script).
+pub fn isdigit(value: Value) -> eval::Result {
+    check_chars("isdigit", value, char::is_number_decimal_digit)
+}
+
+/// Check whether the string is non-empty and consists only of whitespace.
+pub fn isspace(value: Value) -> eval::Result {
+    check_chars("isspace", value, char::is_whitespace)
+}
+
+/// Check whether the string is non-empty and consists only of punctuation.
+pub fn ispunct(value: Value) -> eval::Result {
+    check_chars("ispunct", value, char::is_punctuation)
+}
+
+/// Check whether the string contains letters and all of them are lowercase.
+pub fn islower(value: Value) -> eval::Result {
+    check_cased("islower", value, char::is_lowercase)
+}
+
+/// Check whether the string contains letters and all of them are uppercase.
+pub fn isupper(value: Value) -> eval::Result {
+    check_cased("isupper", value, char::is_uppercase)
+}
+
+
+// Utility functions
+
+/// Split the string into units which it's indexed, sliced, and measured by:
+/// either grapheme clusters, or individual characters.
+pub fn text_units(s: &str, graphemes: bool) -> Vec<&str> {
+    if graphemes {
+        s.graphemes(/* extended grapheme clusters */ true).collect()
+    } else {
+        s.char_indices().map(|(i, c)| &s[i..i + c.len_utf8()]).collect()
+    }
+}
+
+fn check_chars(name: &str, value: Value, pred: fn(char) -> bool) -> eval::Result {
+    eval1!((value: &String) -> Boolean { !value.is_empty() && value.chars().all(pred) });
+    mismatch!(name; ("string") => (value))
+}
+
+fn check_cased(name: &str, value: Value, pred: fn(char) -> bool) -> eval::Result {
+    eval1!((value: &String) -> Boolean {
+        value.chars().any(char::is_alphabetic) &&
+        value.chars().filter(|c| c.is_uppercase() || c.is_lowercase()).all(pred)
+    });
+    mismatch!(name; ("string") => (value))
+}
@@ -0,0 +1,146 @@
+//! Date & time functions.
+//!
+//! Time values are always anchored at a particular UTC offset. They are created
+//! in UTC unless the parsed input specifies otherwise, or unless they're explicitly
+//! converted to a different time zone using tz().
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, UTC};
+use chrono_tz::Tz;
+
+use eval::{self, Error, Value};
+use eval::value::{FloatRepr, IntegerRepr, TimeRepr};
+
+
+/// Return the current time (in UTC).
+pub fn now() -> eval::Result {
+    Ok(Value::Time(utc_to_fixed(UTC::now())))
+}
+
+
+/// Parse a string as time, according to given strftime-like format.
+///
+/// If the format doesn't contain UTC offset directive (like %z),
+/// the time is assumed to be in UTC.
+/// If it doesn't contain any time directives, midnight is assumed.
+pub fn strptime(fmt: Value, string: Value) -> eval::Result {
+    argcheck!("strptime"; ("string", "string") => (fmt, string));
+    let fmt = fmt.unwrap_string();
+    let string = string.unwrap_string();
+
+    if let Ok(t) = DateTime::parse_from_str(&string, &fmt) {
+        return Ok(Value::Time(t));
+    }
+    if let Ok(t) = NaiveDateTime::parse_from_str(&string, &fmt) {
+        return Ok(Value::Time(utc_to_fixed(DateTime::from_utc(t, UTC))));
+    }
+    NaiveDate::parse_from_str(&string, &fmt)
+        .map(|d| Value::Time(utc_to_fixed(DateTime::from_utc(d.and_hms(0, 0, 0), UTC))))
+        .map_err(|e| Error::new(&format!(
+            "cannot parse `{}` as time with format `{}`: {}", string, fmt, e
+        )))
+}
+
+/// Format the time as string, according to given strftime-like format.
+pub fn strftime(fmt: Value, time: Value) -> eval::Result {
+    if let Value::String(ref f) = fmt {
+        // formatting itself would panic on an invalid directive, so check for them first
+        let invalid = StrftimeItems::new(f).any(|item| match item {
+            Item::Error => true,
+            _ => false,
+        });
+        if invalid {
+            return Err(Error::new(&format!("strftime() got invalid time format: {}", f)));
+        }
+    }
+    eval2!((fmt: &String, time: &Time) -> String { format!("{}", time.format(fmt)) });
+    mismatch!("strftime"; ("string", "time") => (fmt, time))
+}
+
+
+/// Convert time to the number of seconds since Unix epoch.
+///
+/// If the time has a fractional second component, the result is a float.
+/// Otherwise it's an integer.
+pub fn epoch(time: Value) -> eval::Result {
+    if let Value::Time(ref t) = time {
+        let nanos = t.timestamp_subsec_nanos();
+        return Ok(if nanos == 0 {
+            Value::Integer(t.timestamp() as IntegerRepr)
+        } else {
+            Value::Float(t.timestamp() as FloatRepr + nanos as FloatRepr / NANOS_PER_SEC)
+        });
+    }
+    mismatch!("epoch"; ("time") => (time))
+}
+
+/// Create a time value from the number of seconds since Unix epoch.
+pub fn fromepoch(secs: Value) -> eval::Result {
+    let timestamp = match secs {
+        Value::Integer(i) => UTC.timestamp_opt(i as i64, 0).single(),
+        Value::Float(f) => {
+            let whole = f.floor();
+            let nanos = ((f - whole) * NANOS_PER_SEC) as u32;
+            UTC.timestamp_opt(whole as i64, nanos).single()
+        },
+        _ => return mismatch!("fromepoch"; ("int") | ("float") => (secs)),
+    };
+    timestamp.map(utc_to_fixed).map(Value::Time)
+        .ok_or_else(|| Error::new(&format!("epoch time out of range: {}", secs)))
+}
+
+
+/// Convert time to given time zone.
+///
+/// The zone can be given either as a name from the tz database
+/// (like "Europe/Warsaw" or "UTC"), or as an offset from UTC in seconds.
+pub fn tz(zone: Value, time: Value) -> eval::Result {
+    if let (&Value::String(ref z), &Value::Time(ref t)) = (&zone, &time) {
+        let zone = try!(z.parse::<Tz>().map_err(|e| Error::new(&format!(
+            "unknown time zone `{}`: {}", z, e
+        ))));
+        let offset = t.with_timezone(&zone).offset().local_minus_utc();
+        return Ok(Value::Time(t.with_timezone(&FixedOffset::east(offset.num_seconds() as i32))));
+    }
+    if let (&Value::Integer(secs), &Value::Time(ref t)) = (&zone, &time) {
+        return FixedOffset::east_opt(secs as i32)
+            .map(|offset| Value::Time(t.with_timezone(&offset)))
+            .ok_or_else(|| Error::new(&format!("invalid UTC offset: {}", secs)));
+    }
+    mismatch!("tz"; ("string", "time") | ("int", "time") => (zone, time))
+}
+
+
+// Time arithmetic
+
+/// Shift the time by given amount of seconds (possibly negative or fractional).
+/// This is used to implement the `+` and `-` operators between time and numbers.
+pub fn shift(time: &TimeRepr, secs: FloatRepr) -> eval::Result {
+    let duration = Duration::nanoseconds((secs * NANOS_PER_SEC) as i64);
+    time.clone().checked_add(duration)
+        .map(Value::Time)
+        .ok_or_else(|| Error::new(&format!(
+            "time out of range after shifting by {} second(s)", secs
+        )))
+}
+
+/// Compute the difference between two times, in seconds.
+/// This is used to implement the `-` operator between time values.
+pub fn difference(left: &TimeRepr, right: &TimeRepr) -> Value {
+    let duration = *left - *right;
+    match duration.num_nanoseconds() {
+        Some(nanos) if nanos % NANOS_PER_SEC as i64 != 0 =>
+            Value::Float(nanos as FloatRepr / NANOS_PER_SEC),
+        _ => Value::Integer(duration.num_seconds() as IntegerRepr),
+    }
+}
+
+
+// Utility functions
+
+const NANOS_PER_SEC: FloatRepr = 1_000_000_000.0;
+
+#[inline]
+fn utc_to_fixed(t: DateTime<UTC>) -> TimeRepr {
+    t.with_timezone(&FixedOffset::east(0))
+}
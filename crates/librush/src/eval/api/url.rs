@@ -0,0 +1,241 @@
+//! URL functions.
+
+use std::mem;
+
+use eval::{self, Error, Value};
+use eval::value::{IntegerRepr, ObjectRepr, StringRepr};
+use super::conv::str_;
+use super::encoding::{percent_decode, percent_encode};
+
+
+/// Split the URL into its components, returning an object with the following keys:
+/// "scheme", "user", "host", "port", "path", "query", and "fragment".
+///
+/// The query string is parsed into an object (as with fromquerystring()).
+/// Any other component that's missing from the URL is nil.
+/// The URL can also be relative, like the request path in a web server's access log.
+pub fn url(value: Value) -> eval::Result {
+    let s = match value {
+        Value::String(s) => s,
+        value => return mismatch!("url"; ("string") => (value)),
+    };
+    let mut rest: &str = &s;
+
+    let fragment = match rest.find('#') {
+        Some(i) => { let f = &rest[i + 1..]; rest = &rest[..i]; Some(f) },
+        None => None,
+    };
+    let query = match rest.find('?') {
+        Some(i) => { let q = &rest[i + 1..]; rest = &rest[..i]; Some(q) },
+        None => None,
+    };
+    let scheme = match url_scheme(rest) {
+        Some(len) => { let s = &rest[..len]; rest = &rest[len + 1..]; Some(s) },
+        None => None,
+    };
+
+    let (mut user, mut host, mut port) = (None, None, None);
+    if rest.starts_with("//") {
+        let authority_len = rest[2..].find('/').map(|i| i + 2).unwrap_or(rest.len());
+        let mut authority = &rest[2..authority_len];
+        rest = &rest[authority_len..];
+
+        if let Some(i) = authority.rfind('@') {
+            user = Some(&authority[..i]);
+            authority = &authority[i + 1..];
+        }
+        // the colon of an IPv6 address (inside brackets) doesn't start the port
+        match authority.rfind(':') {
+            Some(i) if !authority[i..].contains(']') => {
+                let p = &authority[i + 1..];
+                if !p.is_empty() {
+                    port = Some(try!(p.parse::<u16>().map_err(|_| Error::new(&format!(
+                        "url() got invalid port number: {}", p
+                    )))));
+                }
+                authority = &authority[..i];
+            },
+            _ => {},
+        }
+        host = Some(authority);
+    }
+
+    let mut result = ObjectRepr::new();
+    result.insert("scheme".to_owned(), optional_string(scheme));
+    result.insert("user".to_owned(), optional_string(user));
+    result.insert("host".to_owned(), optional_string(host));
+    result.insert("port".to_owned(),
+                  port.map(|p| Value::Integer(p as IntegerRepr)).unwrap_or(Value::Empty));
+    result.insert("path".to_owned(), Value::String(rest.to_owned()));
+    result.insert("query".to_owned(), match query {
+        Some(q) => try!(parse_query("url", q)),
+        None => Value::Object(ObjectRepr::new()),
+    });
+    result.insert("fragment".to_owned(), optional_string(fragment));
+    Ok(Value::Object(result))
+}
+
+/// Assemble an URL from an object of its components,
+/// with the same keys as the ones returned by url().
+///
+/// All the components are optional. The query can be given either as an object,
+/// or as an already encoded query string.
+pub fn urlbuild(value: Value) -> eval::Result {
+    let object = match value {
+        Value::Object(o) => o,
+        value => return mismatch!("urlbuild"; ("object") => (value)),
+    };
+    if let Some(key) = object.keys().find(|k| !URL_COMPONENTS.contains(&k.as_str())) {
+        return Err(Error::new(&format!(
+            "urlbuild() got unknown URL component: {} (expected one of: {})",
+            key, URL_COMPONENTS.join(", ")
+        )));
+    }
+
+    let mut result = String::new();
+    if let Some(scheme) = try!(component(&object, "scheme")) {
+        result.push_str(&scheme);
+        result.push(':');
+    }
+    if let Some(host) = try!(component(&object, "host")) {
+        result.push_str("//");
+        if let Some(user) = try!(component(&object, "user")) {
+            result.push_str(&user);
+            result.push('@');
+        }
+        result.push_str(&host);
+        if let Some(port) = try!(component(&object, "port")) {
+            result.push(':');
+            result.push_str(&port);
+        }
+    }
+    if let Some(path) = try!(component(&object, "path")) {
+        result.push_str(&path);
+    }
+    let query = match object.get("query") {
+        Some(&Value::Object(ref q)) => try!(build_query("urlbuild", q)),
+        _ => try!(component(&object, "query")).unwrap_or_else(String::new),
+    };
+    if !query.is_empty() {
+        result.push('?');
+        result.push_str(&query);
+    }
+    if let Some(fragment) = try!(component(&object, "fragment")) {
+        result.push('#');
+        result.push_str(&fragment);
+    }
+    Ok(Value::String(result))
+}
+
+const URL_COMPONENTS: &'static [&'static str] = &[
+    "scheme", "user", "host", "port", "path", "query", "fragment",
+];
+
+
+/// Encode an object as URL query string, like "foo=1&bar=2".
+///
+/// Array values result in the key being repeated for each of their elements,
+/// while nil values are omitted.
+pub fn querystring(value: Value) -> eval::Result {
+    if let Value::Object(ref o) = value {
+        return build_query("querystring", o).map(Value::String);
+    }
+    mismatch!("querystring"; ("object") => (value))
+}
+
+/// Parse an URL query string (like "foo=1&bar=2") into an object.
+///
+/// Values of keys that occur more than once are collected into arrays.
+pub fn fromquerystring(value: Value) -> eval::Result {
+    if let Value::String(ref s) = value {
+        let query = if s.starts_with('?') { &s[1..] } else { s };
+        return parse_query("fromquerystring", query);
+    }
+    mismatch!("fromquerystring"; ("string") => (value))
+}
+
+
+// Utility functions
+
+/// Determine the length of the URL scheme (like "http") at the beginning of the URL,
+/// if there is one.
+fn url_scheme(s: &str) -> Option<usize> {
+    let colon = match s.find(':') {
+        Some(i) if i > 0 => i,
+        _ => return None,
+    };
+    let scheme = &s[..colon];
+    let valid = scheme.chars().next().map_or(false, |c| c.is_alphabetic() && c.is_digit(36)) &&
+        scheme.chars().all(|c| c.is_digit(36) || c == '+' || c == '-' || c == '.');
+    if valid { Some(colon) } else { None }
+}
+
+fn optional_string(s: Option<&str>) -> Value {
+    s.map(|s| Value::String(s.to_owned())).unwrap_or(Value::Empty)
+}
+
+/// Retrieve an URL component from the object, converted to string.
+fn component(object: &ObjectRepr, key: &str) -> Result<Option<StringRepr>, Error> {
+    match object.get(key) {
+        None | Some(&Value::Empty) => Ok(None),
+        Some(value) => str_(value.clone()).map(|s| Some(s.unwrap_string())).map_err(|_| {
+            Error::new(&format!(
+                "urlbuild() expects a string for URL {}, got {}", key, value.typename()
+            ))
+        }),
+    }
+}
+
+fn parse_query(name: &str, query: &str) -> eval::Result {
+    let mut result = ObjectRepr::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = match pair.find('=') {
+            Some(i) => (&pair[..i], &pair[i + 1..]),
+            None => (pair, ""),
+        };
+        let key = try!(decode_component(name, key));
+        let value = Value::String(try!(decode_component(name, value)));
+
+        // collect the values of repeated keys into an array
+        if !result.contains_key(&key) {
+            result.insert(key, value);
+            continue;
+        }
+        let existing = result.get_mut(&key).unwrap();
+        let previous = mem::replace(existing, Value::Empty);
+        *existing = match previous {
+            Value::Array(mut a) => { a.push(value); Value::Array(a) },
+            first => Value::Array(vec![first, value]),
+        };
+    }
+    Ok(Value::Object(result))
+}
+
+fn decode_component(name: &str, s: &str) -> Result<StringRepr, Error> {
+    let data = try!(percent_decode(name, s));
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+fn build_query(name: &str, object: &ObjectRepr) -> Result<StringRepr, Error> {
+    let mut pairs = Vec::with_capacity(object.len());
+    for (key, value) in object {
+        let values = match *value {
+            Value::Empty => continue,
+            Value::Array(ref a) => a.clone(),
+            ref v => vec![v.clone()],
+        };
+        for v in values {
+            let v = match v {
+                Value::String(s) => s,
+                v @ Value::Boolean(..) | v @ Value::Integer(..) | v @ Value::Float(..) =>
+                    try!(str_(v)).unwrap_string(),
+                v => return Err(Error::new(&format!(
+                    "{}() cannot encode {} as query string value", name, v.typename()
+                ))),
+            };
+            pairs.push(format!("{}={}",
+                               percent_encode(key.as_bytes()), percent_encode(v.as_bytes())));
+        }
+    }
+    Ok(pairs.join("&"))
+}
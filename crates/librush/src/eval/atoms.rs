@@ -1,19 +1,74 @@
 //! Module implementing evaluation of the "atomic" expressions,
 //! i.e. those that create the values that are then operated upon.
 
-use eval::{self, Context, Eval, Value};
+use eval::{self, api, Context, Eval, Function, Value};
+use eval::model::function::Signature;
 use eval::model::value::{ArrayRepr, ObjectRepr};
-use parse::ast::{ArrayNode, ObjectNode, ScalarNode};
+use parse::ast::{ArrayNode, DefinitionNode, LambdaNode, ObjectNode, Params, ScalarNode};
+
+
+/// Name of the variable that holds the raw, unconverted input
+/// (i.e. the current record as string, without any numeric sniffing).
+///
+/// The typed views of the input -- `_b`, `_f` and `_i` -- are derived from it
+/// when they are referenced in an expression.
+pub const RAW_INPUT: &'static str = "_s";
 
 
 /// Evaluate the AST node representing a scalar value.
 impl Eval for ScalarNode {
     #[inline]
     fn eval(&self, context: &mut Context) -> eval::Result {
+        let strict = context.is_strict();
+        self.eval_symbol(context, strict)
+    }
+}
+
+impl ScalarNode {
+    /// Evaluate the scalar value, resolving it if it's a symbol.
+    ///
+    /// Undefined symbols evaluate to their names as strings,
+    /// unless `strict` is true in which case they result in an error.
+    fn eval_symbol(&self, context: &Context, strict: bool) -> eval::Result {
+        if let Value::Symbol(ref sym) = self.value {
+            if !context.is_defined(sym) {
+                if let Some(result) = eval_typed_input(sym, context) {
+                    return result;
+                }
+                if strict {
+                    return Err(undefined_name(sym, context));
+                }
+            }
+        }
         Ok(context.resolve(&self.value))
     }
 }
 
+/// Evaluate an expression that's used as a key, like an object attribute name.
+///
+/// Bare words are clearly meant to be strings here,
+/// so they are accepted as such even if the Context is strict.
+/// This includes the names of functions (which could never be keys)
+/// and built-in constants, so that e.g. `{log: 1}` or `_[pi]` work as expected.
+pub fn eval_key(expr: &Box<Eval>, context: &mut Context) -> eval::Result {
+    match expr.downcast_ref::<ScalarNode>() {
+        Some(scalar) => {
+            if let Value::Symbol(ref sym) = scalar.value {
+                let is_name = match context.get(sym) {
+                    Some(&Value::Function(..)) => true,
+                    Some(value) => api::is_constant(sym, value),
+                    None => false,
+                };
+                if is_name {
+                    return Ok(Value::String(sym.clone()));
+                }
+            }
+            scalar.eval_symbol(context, false)
+        },
+        None => expr.eval(context),
+    }
+}
+
 
 /// Evaluate the AST node representing an array value.
 impl Eval for ArrayNode {
@@ -33,10 +88,11 @@ impl Eval for ObjectNode {
     fn eval(&self, context: &mut Context) -> eval::Result {
         let mut attrs = ObjectRepr::with_capacity(self.attributes.len());
         for &(ref k, ref v) in &self.attributes {
-            let key = try!(k.eval(context));
+            let key = try!(eval_key(k, context));
             let value = try!(v.eval(context));
             if let Value::String(attr) = key {
-                attrs.insert(attr, value);
+                // duplicate keys retain the position of their first occurrence
+                api::objects::set_attr(&mut attrs, attr, value);
             } else {
                 return Err(eval::Error::new(&format!(
                     "object attribute name must be string, got {}", key.typename()
@@ -46,3 +102,76 @@ impl Eval for ObjectNode {
         Ok(Value::Object(attrs))
     }
 }
+
+
+/// Evaluate the AST node representing a lambda expression.
+impl Eval for LambdaNode {
+    fn eval(&self, context: &mut Context) -> eval::Result {
+        let signature = try!(eval_params(&self.params, context));
+        let closure = context.capture();
+        Ok(Value::Function(
+            Function::from_lambda(signature, self.body.clone(), closure)
+        ))
+    }
+}
+
+
+/// Evaluate the AST node representing a named function definition.
+impl Eval for DefinitionNode {
+    fn eval(&self, context: &mut Context) -> eval::Result {
+        let signature = try!(eval_params(&self.params, context));
+        let closure = context.capture();
+        let func = Function::from_definition(
+            self.name.clone(), signature, self.body.clone(), closure);
+        context.set(&self.name, Value::Function(func));
+        Ok(Value::Empty)
+    }
+}
+
+
+// Utility functions
+
+/// Evaluate the parameters of a user-defined function,
+/// which means evaluating the expressions for their default values.
+fn eval_params(params: &Params, context: &mut Context) -> Result<Signature, eval::Error> {
+    let mut names = Vec::with_capacity(params.names.len());
+    for &(ref name, ref default) in &params.names {
+        let default = match *default {
+            Some(ref d) => Some(try!(d.eval(context))),
+            None => None,
+        };
+        names.push((name.clone(), default));
+    }
+    Ok(Signature::new(names, params.rest.clone()))
+}
+
+/// Create the error for referring to an undefined name,
+/// suggesting any similar names that are defined.
+fn undefined_name(name: &str, context: &Context) -> eval::Error {
+    let similar: Vec<_> = context.similar_names(name).into_iter()
+        .map(|n| format!("`{}`", n)).collect();
+    let suggestion = match similar.len() {
+        0 => "".to_owned(),
+        1 => format!(" (did you mean {}?)", similar[0]),
+        _ => format!(" (did you mean one of: {}?)", similar.join(", ")),
+    };
+    eval::Error::new(&format!("`{}` is not defined{}", name, suggestion))
+}
+
+/// Evaluate one of the typed views of the raw input (`_b`, `_f` or `_i`)
+/// by forcibly converting it to the corresponding type.
+///
+/// Returns None if the symbol isn't such a view, or if there is no input to convert.
+fn eval_typed_input(name: &str, context: &Context) -> Option<eval::Result> {
+    let convert: fn(Value) -> eval::Result = match name {
+        "_b" => api::conv::bool,
+        "_f" => api::conv::float,
+        "_i" => api::conv::int,
+        _ => return None,
+    };
+    context.get(RAW_INPUT).cloned().map(|input| match input {
+        Value::Array(a) => a.into_iter().map(convert)
+            .collect::<Result<ArrayRepr, _>>().map(Value::Array),
+        v => convert(v),
+    })
+}
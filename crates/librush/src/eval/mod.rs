@@ -18,9 +18,11 @@ mod atoms;
 mod operators;
 mod trailers;
 
-pub use self::model::{Context, Function, Invoke, Value};
+pub use self::atoms::RAW_INPUT;
+pub use self::model::{Context, Function, Invoke, Value, DEFAULT_MAX_DEPTH};
 pub use self::model::Error;
 pub use self::model::value;  // for *Repr typedefs
+pub use self::api::conv::bool as truthy;  // for filtering input
 
 
 use std::fmt;
@@ -14,6 +14,9 @@ use super::value::Value;
 /// Arguments to a function.
 pub type Args = Vec<Value>;
 
+/// Keyword arguments to a function, i.e. ones passed together with parameter names.
+pub type KwArgs = Vec<(String, Value)>;
+
 /// Type for a number of arguments
 /// (both expected by a function, and actually passed).
 pub type ArgCount = usize;
@@ -73,7 +76,7 @@ impl Arity {
     #[inline]
     pub fn minimum(&self) -> ArgCount {
         match *self {
-            Arity::Exact(c) | Arity::Minimum(c) | Arity::Range(_, c) => c,
+            Arity::Exact(c) | Arity::Minimum(c) | Arity::Range(c, _) => c,
         }
     }
 
@@ -265,14 +268,11 @@ impl Sub<ArgCount> for Arity {
                     c, rhs)
             },
             Arity::Range(a, b) => {
-                let span = b - a;
-                if rhs < span {
-                    return Arity::Range(a, b - rhs);
-                } else if rhs == span {
-                    return Arity::Exact(a);
+                if rhs <= b {
+                    return Arity::with_range(a.saturating_sub(rhs), b - rhs);
                 }
                 panic!("underflow when subtracting from arity range: \
-                    ({} - {}) - {} < 0", b, a, rhs)
+                    {} - {} < 0", b, rhs)
             },
         }
     }
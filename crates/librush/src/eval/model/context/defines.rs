@@ -215,6 +215,19 @@ impl<'c> Context<'c> {
     }
 }
 
+impl<'c> Context<'c> {
+    /// Define a regular function taking two arguments, the second of which is optional.
+    pub fn define_binary_opt<N: ?Sized, F>(&mut self, name: &'static N, func: F) -> &mut Self
+        where Name: Borrow<N>, N: ToOwned<Owned=Name> + Hash + Eq + Display,
+              F: Fn(Value, Option<Value>) -> eval::Result + 'static
+    {
+        self.define(name, Arity::with_range(1, 2), move |args: Args| {
+            let mut args = args.into_iter();
+            func(args.next().unwrap(), args.next())
+        })
+    }
+}
+
 impl<'c> Context<'c> {
     /// Define a regular function taking at most two arguments.
     pub fn define_upto_binary<N: ?Sized, F>(&mut self, name: &'static N, func: F) -> &mut Self
@@ -10,14 +10,17 @@ mod defines;
 
 
 use std::borrow::{Borrow, ToOwned};
+use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::hash::{BuildHasherDefault, Hash};
+use std::rc::Rc;
 
 use fnv::FnvHasher;
 
 use eval;
 use super::{Args, Invoke, Value};
+use super::value::Coercion;
 
 
 /// Type for names of variables present in the Context.
@@ -27,21 +30,60 @@ pub type Name = String;
 /// Uses the Fowler-Noll-Vo hashing algorithm which is faster for short keys.
 type Hasher = BuildHasherDefault<FnvHasher>;
 
+/// Type for the mapping of names to values in a Context.
+pub type Scope = HashMap<Name, Value, Hasher>;
+
+
+/// Default limit on the depth of nested calls to user-defined functions.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Maximum number of similar names to suggest when an undefined one is referred to.
+const MAX_SUGGESTIONS: usize = 3;
+
 
 /// Evaluation context for an expression.
-#[derive(Default)]
 pub struct Context<'c> {
     /// Optional parent Context, i.e. a lower "frame" on the "stack".
     parent: Option<&'c Context<'c>>,
 
+    /// Optional names & values captured by a closure
+    /// whose invocation this Context has been created for.
+    closure: Option<Rc<Scope>>,
+
     /// Names & values present in the context.
-    scope: HashMap<Name, Value, Hasher>,
+    scope: Scope,
+
+    /// Number of nested calls to user-defined functions
+    /// that have led to this Context.
+    depth: usize,
+    /// Maximum allowed depth of such calls.
+    max_depth: usize,
+
+    /// Whether referring to undefined names is an error
+    /// (rather than them being treated as strings).
+    strict: bool,
+    /// Types that the input strings are converted to when they look like such values.
+    coercion: Coercion,
+    /// Whether strings are measured, indexed, and sliced by grapheme clusters
+    /// (rather than individual characters).
+    graphemes: bool,
+    /// Whether the input line endings are written back verbatim after each result
+    /// (rather than always a single \n).
+    keep_line_endings: bool,
+}
+
+impl<'c> Default for Context<'c> {
+    fn default() -> Self {
+        Context{parent: None, closure: None, scope: Scope::default(),
+                depth: 0, max_depth: DEFAULT_MAX_DEPTH, strict: false,
+                coercion: Coercion::default(), graphemes: false, keep_line_endings: false}
+    }
 }
 
 impl<'c> Context<'c> {
     /// Create a new root context.
     pub fn new() -> Context<'c> {
-        let mut context = Context{parent: None, scope: HashMap::default()};
+        let mut context = Context::default();
         context.init_builtins();
         context
     }
@@ -49,7 +91,97 @@ impl<'c> Context<'c> {
     /// Create a new Context that's a child of given parent.
     #[inline]
     pub fn with_parent(parent: &'c Context<'c>) -> Context<'c> {
-        Context{parent: Some(parent), scope: HashMap::default()}
+        Context{parent: Some(parent), closure: None, scope: Scope::default(),
+                depth: parent.depth, max_depth: parent.max_depth, strict: parent.strict,
+                coercion: parent.coercion, graphemes: parent.graphemes,
+                keep_line_endings: parent.keep_line_endings}
+    }
+
+    /// Create a new Context for invoking a closure from given caller's Context.
+    ///
+    /// The new Context is a child of the root context rather than the caller's,
+    /// so the caller's variables are not visible inside. Names captured by the closure
+    /// are visible, however, and they shadow the globals from the root.
+    ///
+    /// Fails if the maximum depth of nested calls would be exceeded.
+    pub fn with_closure(caller: &'c Context<'c>,
+                        closure: Rc<Scope>) -> Result<Context<'c>, eval::Error> {
+        let depth = caller.depth + 1;
+        if depth > caller.max_depth {
+            return Err(eval::Error::new(&format!(
+                "maximum recursion depth exceeded ({})", caller.max_depth
+            )));
+        }
+        Ok(Context{parent: Some(caller.root()), closure: Some(closure), scope: Scope::default(),
+                   depth: depth, max_depth: caller.max_depth, strict: caller.strict,
+                   coercion: caller.coercion, graphemes: caller.graphemes,
+                   keep_line_endings: caller.keep_line_endings})
+    }
+
+    /// Set the maximum depth of nested calls to user-defined functions.
+    /// Exceeding it results in an evaluation error.
+    #[inline]
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Set whether the Context is strict, i.e. whether referring to an undefined name
+    /// results in an evaluation error rather than the name being treated as a string.
+    #[inline]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Whether the Context is strict about referring to undefined names.
+    #[inline]
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Set the types that input strings should be coerced to
+    /// if they look like values of those types.
+    ///
+    /// Use `Coercion::none()` to always keep the input as strings.
+    #[inline]
+    pub fn set_coercion(&mut self, coercion: Coercion) {
+        self.coercion = coercion;
+    }
+
+    /// Coercion that's applied to input strings.
+    #[inline]
+    pub fn coercion(&self) -> Coercion {
+        self.coercion
+    }
+
+    /// Set whether the length of strings, as well as their indices and slices,
+    /// should refer to grapheme clusters rather than individual characters.
+    ///
+    /// This makes a difference for text containing combining marks or emoji sequences.
+    #[inline]
+    pub fn set_graphemes(&mut self, graphemes: bool) {
+        self.graphemes = graphemes;
+    }
+
+    /// Whether strings are treated as sequences of grapheme clusters.
+    #[inline]
+    pub fn uses_graphemes(&self) -> bool {
+        self.graphemes
+    }
+
+    /// Set whether the line endings of input should be reproduced verbatim in the output
+    /// when processing it line by line, instead of every result being followed by \n.
+    ///
+    /// Together with `Coercion::none()`, this makes an identity expression
+    /// reproduce its input exactly.
+    #[inline]
+    pub fn set_keep_line_endings(&mut self, keep: bool) {
+        self.keep_line_endings = keep;
+    }
+
+    /// Whether the input line endings are reproduced verbatim in the output.
+    #[inline]
+    pub fn keeps_line_endings(&self) -> bool {
+        self.keep_line_endings
     }
 
     /// Whether this is a root context (one without a parent).
@@ -58,6 +190,16 @@ impl<'c> Context<'c> {
         self.parent.is_none()
     }
 
+    /// Return the root context, i.e. the bottom "frame" of the "stack"
+    /// where builtins and global variables are defined.
+    pub fn root(&self) -> &Context<'c> {
+        let mut context = self;
+        while let Some(parent) = context.parent {
+            context = parent;
+        }
+        context
+    }
+
     /// Whether this context is empty, i.e. has no own symbols defined in its scope.
     /// Note that because of builtins, the root context will never be empty!
     #[inline]
@@ -71,9 +213,7 @@ impl<'c> Context<'c> {
     pub fn is_defined<N: ?Sized>(&self, name: &N) -> bool
         where Name: Borrow<N>, N: Hash + Eq
     {
-        self.scope.get(name)
-            .or_else(|| self.parent.and_then(|ctx| ctx.get(name)))
-            .is_some()
+        self.get(name).is_some()
     }
 
     /// Check if given name is defined in this context.
@@ -92,9 +232,56 @@ impl<'c> Context<'c> {
         where Name: Borrow<N>, N: Hash + Eq
     {
         self.scope.get(name)
+            .or_else(|| self.closure.as_ref().and_then(|c| c.get(name)))
             .or_else(|| self.parent.and_then(|ctx| ctx.get(name)))
     }
 
+    /// Find the names visible in this context that are similar to given one,
+    /// e.g. to suggest them when the name has been mistyped.
+    ///
+    /// Returned names are ordered from the most similar.
+    pub fn similar_names(&self, name: &str) -> Vec<&str> {
+        let max_distance = max(1, name.chars().count() / 2);
+
+        let mut candidates = Vec::new();
+        let mut context = Some(self);
+        while let Some(ctx) = context {
+            let closure = ctx.closure.iter().flat_map(|c| c.keys());
+            for candidate in ctx.scope.keys().chain(closure) {
+                let distance = edit_distance(name, candidate);
+                if 0 < distance && distance <= max_distance {
+                    candidates.push((distance, candidate as &str));
+                }
+            }
+            context = ctx.parent;
+        }
+
+        candidates.sort();
+        candidates.dedup();
+        candidates.into_iter().map(|(_, c)| c).take(MAX_SUGGESTIONS).collect()
+    }
+
+    /// Capture the names & values that are visible in this context
+    /// for the purpose of creating a closure.
+    ///
+    /// The root context is omitted, as its globals are always accessible
+    /// when the closure is invoked later.
+    pub fn capture(&self) -> Scope {
+        let mut result = Scope::default();
+        let mut context = self;
+        while let Some(parent) = context.parent {
+            let closure = context.closure.iter().flat_map(|c| c.iter());
+            for (name, value) in context.scope.iter().chain(closure) {
+                // names from inner contexts shadow those from outer ones
+                if !result.contains_key(name) {
+                    result.insert(name.clone(), value.clone());
+                }
+            }
+            context = parent;
+        }
+        result
+    }
+
     /// Set a value for a variable inside the context's scope.
     /// If the name already exists in the parent scope (if any),
     /// it will be shadowed.
@@ -162,3 +349,27 @@ impl<'c> Context<'c> {
         }
     }
 }
+
+
+// Utility functions
+
+/// Compute the edit (Levenshtein) distance between two strings,
+/// i.e. the number of single character insertions, deletions, or substitutions
+/// required to change one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    // only a single row of the distance matrix is needed at any time
+    let mut row: Vec<usize> = (0..b.len() + 1).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb { diagonal }
+                         else { 1 + min(diagonal, min(above, row[j])) };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
@@ -8,8 +8,10 @@ use std::cmp::PartialEq;
 use std::fmt;
 use std::rc::Rc;
 
-use eval::{self, Context, Eval};
-use super::arity::{Args, Arity};
+use eval::{self, api, Context, Eval};
+use parse::ast::{ConditionalNode, FunctionCallNode, ScalarNode};
+use super::arity::{Args, Arity, KwArgs};
+use super::context::Scope;
 use super::value::Value;
 
 
@@ -97,17 +99,26 @@ impl Function {
         Function::NativeCtx(arity, Rc::new(f))
     }
 
-    /// Create the Function struct from a lambda expression.
+    /// Create the Function struct from a lambda expression
+    /// and the names & values it has captured from its defining scope.
     #[inline]
-    pub fn from_lambda(argnames: Vec<String>, body: Box<Eval>) -> Function {
-        Function::Custom(CustomFunction::new(argnames, body))
+    pub fn from_lambda(signature: Signature, body: Rc<Box<Eval>>, closure: Scope) -> Function {
+        Function::Custom(CustomFunction::new(signature, body, closure))
+    }
+
+    /// Create the Function struct from a named function definition
+    /// and the names & values it has captured from its defining scope.
+    #[inline]
+    pub fn from_definition(name: String, signature: Signature,
+                           body: Rc<Box<Eval>>, closure: Scope) -> Function {
+        Function::Custom(CustomFunction::with_name(name, signature, body, closure))
     }
 
     /// Function composition:
     /// self.compose_with(other)(x) === self(other(x))
     #[inline]
     pub fn compose_with(self, other: Function) -> Option<Function> {
-        if self.arity() == 1 {
+        if self.arity().accepts(1) {
             let arity = other.arity();
             let result = move |args, context: &Context| {
                 let intermediate = try!(other.invoke(args, &context));
@@ -118,6 +129,26 @@ impl Function {
         None
     }
 
+    /// Bind keyword arguments to the function:
+    /// self.bind_kwargs([(name, arg)])(x) === self(x, name=arg)
+    ///
+    /// Only custom functions accept keyword arguments.
+    pub fn bind_kwargs(self, kwargs: KwArgs) -> Result<Function, eval::Error> {
+        match self {
+            Function::Custom(f) => {
+                try!(f.signature.check_kwargs(&kwargs));
+                let arity = f.signature.arity(&kwargs);
+                let result = move |args, context: &Context| {
+                    f.invoke_kw(args, &kwargs, &context)
+                };
+                Ok(Function::from_native_ctx(arity, result))
+            },
+            other => Err(eval::Error::new(&format!(
+                "only user-defined functions accept keyword arguments, got {:?}", other
+            ))),
+        }
+    }
+
     /// Function currying (partial application):
     /// self.curry(arg)(x) === self(arg, x)
     #[inline]
@@ -190,48 +221,248 @@ pub type NativeCtxFunction = Fn(Args, &Context) -> eval::Result;
 
 /// Custom function type,
 /// i.e. one that has been defined using the expression syntax.
+///
+/// Such functions are closures: they remember the variables that were in scope
+/// at the point of their definition, and have access to those (rather than
+/// the caller's variables) when they're invoked.
+///
+/// Functions defined with `def` also have a name, which is always bound
+/// to the function itself within its body, allowing for recursion.
 #[derive(Clone)]
 pub struct CustomFunction {
-    argnames: Vec<String>,
+    name: Option<String>,
+    signature: Signature,
     expr: Rc<Box<Eval>>,
+    closure: Rc<Scope>,
 }
 
 impl CustomFunction {
     #[inline]
-    pub fn new(argnames: Vec<String>, expr: Box<Eval>) -> CustomFunction {
+    pub fn new(signature: Signature, expr: Rc<Box<Eval>>, closure: Scope) -> CustomFunction {
         CustomFunction{
-            argnames: argnames,
-            expr: Rc::new(expr),
+            name: None,
+            signature: signature,
+            expr: expr,
+            closure: Rc::new(closure),
         }
     }
+
+    #[inline]
+    pub fn with_name(name: String, signature: Signature,
+                     expr: Rc<Box<Eval>>, closure: Scope) -> CustomFunction {
+        CustomFunction{name: Some(name), ..CustomFunction::new(signature, expr, closure)}
+    }
 }
 
 impl fmt::Debug for CustomFunction {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "|{}| {:?}", self.argnames.join(","), self.expr)
+        match self.name {
+            Some(ref name) => write!(fmt, "{}({}) = {:?}", name, self.signature, self.expr),
+            None => write!(fmt, "|{}| {:?}", self.signature, self.expr),
+        }
     }
 }
 
 impl Invoke for CustomFunction {
     #[inline]
     fn arity(&self) -> Arity {
-        Arity::Exact(self.argnames.len())
+        self.signature.arity(&[])
     }
 
+    #[inline]
     fn invoke(&self, args: Args, context: &Context) -> eval::Result {
-        let expected_count = self.argnames.len();
-        let actual_count = args.len();
-        if actual_count != expected_count {
+        self.invoke_kw(args, &[], context)
+    }
+}
+
+impl CustomFunction {
+    /// Invoke the function with both positional and keyword arguments.
+    pub fn invoke_kw(&self, args: Args,
+                     kwargs: &[(String, Value)], context: &Context) -> eval::Result {
+        let mut bindings = try!(self.signature.bind(args, kwargs));
+
+        // Evaluate the function body in a "frame" based directly on the root
+        // (so that globals are available), but not on the caller's context.
+        // Any non-global variables come from the closure instead.
+        //
+        // If the body ends with a call to the function itself, we don't recurse
+        // but simply evaluate the body again with new arguments.
+        loop {
+            let mut frame = try!(Context::with_closure(context, self.closure.clone()));
+            if let Some(ref name) = self.name {
+                frame.set(name, Value::Function(Function::Custom(self.clone())));
+            }
+            for (name, value) in bindings {
+                frame.set(&name, value);
+            }
+            match try!(self.eval_tail(&self.expr, &mut frame)) {
+                Tail::Return(result) => return Ok(result),
+                Tail::Recur(args) => bindings = try!(self.signature.bind(args, &[])),
+            }
+        }
+    }
+}
+
+impl CustomFunction {
+    /// Evaluate an expression that's in tail position within the function's body.
+    ///
+    /// If it turns out to be a call to the function itself with the right number
+    /// of arguments, those arguments are returned instead of making the call.
+    fn eval_tail(&self, expr: &Box<Eval>, context: &mut Context) -> Result<Tail, eval::Error> {
+        if let Some(cond) = expr.downcast_ref::<ConditionalNode>() {
+            let condition = try!(
+                cond.cond.eval(context).and_then(api::conv::bool)
+            ).unwrap_bool();
+            let branch = if condition { &cond.then } else { &cond.else_ };
+            return self.eval_tail(branch, context);
+        }
+
+        if let Some(call) = expr.downcast_ref::<FunctionCallNode>() {
+            if self.is_self_call(call) {
+                let mut args = Vec::with_capacity(call.args.len());
+                for arg in &call.args {
+                    args.push(try!(arg.eval(context)));
+                }
+                return Ok(Tail::Recur(args));
+            }
+        }
+
+        expr.eval(context).map(Tail::Return)
+    }
+
+    /// Whether given function call node is a direct call to this very function
+    /// with all of its arguments.
+    fn is_self_call(&self, call: &FunctionCallNode) -> bool {
+        let name = match self.name {
+            Some(ref n) => n,
+            None => return false,
+        };
+        if self.signature.has_param(name) {
+            return false;  // name shadowed by an argument
+        }
+        if !call.kwargs.is_empty() || !self.arity().accepts(call.args.len()) {
+            return false;  // not a plain, complete call
+        }
+        match call.func.downcast_ref::<ScalarNode>() {
+            Some(&ScalarNode{value: Value::Symbol(ref sym)}) => sym == name,
+            _ => false,
+        }
+    }
+}
+
+/// Result of evaluating the tail expression of a custom function's body.
+enum Tail {
+    /// Final result of the function.
+    Return(Value),
+    /// Arguments of a self-recursive call in tail position.
+    Recur(Args),
+}
+
+
+/// Signature of a custom function, i.e. the parameters it accepts.
+#[derive(Clone)]
+pub struct Signature {
+    /// Names of regular parameters, together with their default values (if any).
+    params: Vec<(String, Option<Value>)>,
+    /// Name of the parameter which gathers any remaining arguments into an array.
+    rest: Option<String>,
+}
+
+impl Signature {
+    #[inline]
+    pub fn new(params: Vec<(String, Option<Value>)>, rest: Option<String>) -> Signature {
+        Signature{params: params, rest: rest}
+    }
+
+    /// Whether there is a parameter of given name.
+    pub fn has_param(&self, name: &str) -> bool {
+        self.params.iter().any(|&(ref p, _)| p == name)
+            || self.rest.as_ref().map_or(false, |r| r == name)
+    }
+
+    /// Arity of a function with this signature
+    /// once given keyword arguments have been bound.
+    pub fn arity(&self, kwargs: &[(String, Value)]) -> Arity {
+        let mut required = 0;
+        let mut total = 0;
+        for &(ref name, ref default) in &self.params {
+            if kwargs.iter().any(|&(ref k, _)| k == name) {
+                continue;  // already bound
+            }
+            total += 1;
+            if default.is_none() {
+                required += 1;
+            }
+        }
+        if self.rest.is_some() {
+            Arity::with_minimum(required)
+        } else {
+            Arity::with_range(required, total)
+        }
+    }
+
+    /// Check that given keyword arguments are valid for this signature.
+    pub fn check_kwargs(&self, kwargs: &[(String, Value)]) -> Result<(), eval::Error> {
+        for (i, &(ref name, _)) in kwargs.iter().enumerate() {
+            if !self.params.iter().any(|&(ref p, _)| p == name) {
+                return Err(eval::Error::new(&format!(
+                    "unexpected keyword argument: {}", name
+                )));
+            }
+            if kwargs[..i].iter().any(|&(ref k, _)| k == name) {
+                return Err(eval::Error::new(&format!(
+                    "duplicate keyword argument: {}", name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bind positional and keyword arguments to the parameters,
+    /// filling in default values where necessary.
+    ///
+    /// Positional arguments go to the parameters that haven't been given
+    /// as keyword arguments, in order.
+    /// Returns the names of all parameters together with their values.
+    pub fn bind(&self, args: Args,
+                kwargs: &[(String, Value)]) -> Result<Vec<(String, Value)>, eval::Error> {
+        try!(self.check_kwargs(kwargs));
+        let arity = self.arity(kwargs);
+        if !arity.accepts(args.len()) {
             return Err(eval::Error::new(&format!(
-                "function expects {} argument(s), got {}",
-                expected_count, actual_count
+                "function expects {} argument(s), got {}", arity, args.len()
             )));
         }
 
-        let mut context = Context::with_parent(context);
-        for (name, value) in self.argnames.iter().zip(args.into_iter()) {
-            context.set(name, value);
+        let mut args = args.into_iter();
+        let mut result = Vec::with_capacity(self.params.len() + 1);
+        for &(ref name, ref default) in &self.params {
+            let kwarg = kwargs.iter().find(|&&(ref k, _)| k == name);
+            let value = match (kwarg, default) {
+                (Some(&(_, ref v)), _) => v.clone(),
+                (None, &Some(ref d)) => args.next().unwrap_or_else(|| d.clone()),
+                (None, &None) => args.next().unwrap(),  // guaranteed by arity check
+            };
+            result.push((name.clone(), value));
+        }
+        if let Some(ref rest) = self.rest {
+            result.push((rest.clone(), Value::Array(args.collect())));
+        }
+        Ok(result)
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut params: Vec<_> = self.params.iter()
+            .map(|&(ref name, ref default)| match *default {
+                Some(ref d) => format!("{}={:?}", name, d),
+                None => name.clone(),
+            })
+            .collect();
+        if let Some(ref rest) = self.rest {
+            params.push(format!("*{}", rest));
         }
-        self.expr.eval(&mut context)
+        write!(fmt, "{}", params.join(","))
     }
 }
@@ -8,8 +8,8 @@ mod error;
 pub mod function;
 pub mod value;
 
-pub use self::arity::{Args, ArgCount, Arity};
-pub use self::context::{Context, Name};
+pub use self::arity::{Args, ArgCount, Arity, KwArgs};
+pub use self::context::{Context, Name, Scope, DEFAULT_MAX_DEPTH};
 pub use self::error::Error;
 pub use self::function::{Function, Invoke};
 pub use self::value::Value;
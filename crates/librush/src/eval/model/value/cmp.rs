@@ -19,6 +19,7 @@ impl TryOrd for Value {
             (&Value::Float(a), &Value::Float(b)) => a.partial_cmp(&b),
 
             (&Value::String(ref a), &Value::String(ref b)) => a.partial_cmp(b),
+            (&Value::Time(ref a), &Value::Time(ref b)) => a.partial_cmp(b),
             // TODO(xion): consider implementing ordering of arrays, too
 
             _ => None,
@@ -44,8 +45,16 @@ impl TryEq for Value {
             // others
             (&Value::Boolean(a), &Value::Boolean(b)) => Ok(a == b),
             (&Value::String(ref a), &Value::String(ref b)) => Ok(a == b),
+            (&Value::Time(ref a), &Value::Time(ref b)) => Ok(a == b),
             (&Value::Array(ref a), &Value::Array(ref b)) => Ok(a == b),
-            (&Value::Object(ref a), &Value::Object(ref b)) => Ok(a == b),
+            // (objects are equal regardless of the order of their keys)
+            (&Value::Object(ref a), &Value::Object(ref b)) => Ok(
+                a.len() == b.len() && a.iter().all(|(k, v)| b.get(k) == Some(v))
+            ),
+
+            // nil is only equal to itself, but can be compared with anything
+            (&Value::Empty, &Value::Empty) => Ok(true),
+            (&Value::Empty, _) | (_, &Value::Empty) => Ok(false),
 
             _ => Err(eval::Error::new(&format!(
                 "cannot compare {} with {}", self.typename(), other.typename()
@@ -27,6 +27,7 @@ value_from!(BooleanRepr => Boolean);
 value_from!(IntegerRepr => Integer);
 value_from!(FloatRepr => Float);
 value_from!(RegexRepr => Regex);
+value_from!(TimeRepr => Time);
 value_from!(ArrayRepr => Array);
 value_from!(ObjectRepr => Object);
 value_from!(FunctionRepr => Function);
@@ -57,15 +58,140 @@ impl FromStr for Value {
     /// Create a Value from string, reinterpreting input as number
     /// if we find out it's in numeric form.
     fn from_str(s: &str) -> Result<Value, Self::Err> {
-        if let Ok(int) = s.parse::<IntegerRepr>() {
-            return Ok(Value::Integer(int));
+        Ok(Coercion::all().apply(s.to_owned()))
+    }
+}
+
+
+/// Selection of types that input strings are coerced to
+/// if they look like values of those types (e.g. "42" is coerced to an integer).
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub struct Coercion {
+    pub int: bool,
+    pub float: bool,
+    pub bool: bool,
+    /// Integers in the other forms accepted by the expression syntax,
+    /// like "0x1F", "0o17", "0b101", or "1_000".
+    pub radix: bool,
+}
+
+impl Coercion {
+    /// Coercion to all the supported types.
+    ///
+    /// Prefixed integers (like hexadecimal "0x1F") are not included
+    /// and have to be requested explicitly.
+    #[inline]
+    pub fn all() -> Coercion {
+        Coercion{int: true, float: true, bool: true, radix: false}
+    }
+
+    /// No coercion at all, i.e. strings are always kept as strings.
+    #[inline]
+    pub fn none() -> Coercion {
+        Coercion{int: false, float: false, bool: false, radix: false}
+    }
+
+    /// Whether this doesn't coerce strings to any other type.
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        *self == Coercion::none()
+    }
+
+    /// Convert a string into Value, reinterpreting it as any of the selected types
+    /// if it's in the corresponding form.
+    pub fn apply(&self, s: String) -> Value {
+        if self.int {
+            if let Ok(int) = s.parse::<IntegerRepr>() {
+                return Value::Integer(int);
+            }
         }
-        if let Ok(float) = s.parse::<FloatRepr>() {
-            return Ok(Value::Float(float));
+        if self.radix {
+            if let Some(int) = parse_int(&s) {
+                return Value::Integer(int);
+            }
         }
-        if let Ok(boolean) = s.parse::<BooleanRepr>() {
-            return Ok(Value::Boolean(boolean));
+        if self.float {
+            if let Ok(float) = s.parse::<FloatRepr>() {
+                return Value::Float(float);
+            }
         }
-        Ok(Value::String(s.to_owned()))
+        if self.bool {
+            if let Ok(boolean) = s.parse::<BooleanRepr>() {
+                return Value::Boolean(boolean);
+            }
+        }
+        Value::String(s)
     }
 }
+
+impl Default for Coercion {
+    fn default() -> Self { Coercion::all() }
+}
+
+impl FromStr for Coercion {
+    type Err = String;
+
+    /// Parse a comma-separated list of type names, like "int,float".
+    /// Empty string means no coercion at all.
+    fn from_str(s: &str) -> Result<Coercion, Self::Err> {
+        let mut result = Coercion::none();
+        for name in s.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "int" => result.int = true,
+                "float" => result.float = true,
+                "bool" => result.bool = true,
+                "radix" => result.radix = true,
+                _ => return Err(format!(
+                    "unknown type to coerce input to: {} (expected int, float, bool, or radix)",
+                    name
+                )),
+            }
+        }
+        Ok(result)
+    }
+}
+
+
+/// Parse an integer in any of the forms accepted by the expression syntax:
+/// decimal, or hexadecimal/octal/binary with the "0x"/"0o"/"0b" prefix.
+/// Digits may be separated with single underscores, as in "1_000_000".
+pub fn parse_int(s: &str) -> Option<IntegerRepr> {
+    let digits = s.trim_left_matches(|c| c == '+' || c == '-');
+    let radix = RADIX_PREFIXES.iter()
+        .find(|&&(_, prefix)| has_prefix(digits, prefix))
+        .map(|&(radix, _)| radix)
+        .unwrap_or(10);
+    parse_int_radix(s, radix)
+}
+
+/// Parse an integer in given radix (between 2 and 36).
+///
+/// Like in parse_int(), digits may be separated with single underscores.
+/// For radix 16, 8, and 2, the number may also have the corresponding prefix.
+pub fn parse_int_radix(s: &str, radix: u32) -> Option<IntegerRepr> {
+    let (negative, mut digits) = match s.chars().next() {
+        Some('-') => (true, &s[1..]),
+        Some('+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    if let Some(&(_, prefix)) = RADIX_PREFIXES.iter().find(|&&(r, _)| r == radix) {
+        if has_prefix(digits, prefix) {
+            digits = &digits[prefix.len()..];
+        }
+    }
+
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_')
+            || digits.contains("__") {
+        return None;
+    }
+    let mut number = if negative { "-".to_owned() } else { String::new() };
+    number.extend(digits.chars().filter(|&c| c != '_'));
+    IntegerRepr::from_str_radix(&number, radix).ok()
+}
+
+const RADIX_PREFIXES: &'static [(u32, &'static str)] = &[(16, "0x"), (8, "0o"), (2, "0b")];
+
+/// Check for a (case-insensitive) radix prefix of a number.
+fn has_prefix(s: &str, prefix: &str) -> bool {
+    s.starts_with(prefix) || s.starts_with(&prefix.to_uppercase() as &str)
+}
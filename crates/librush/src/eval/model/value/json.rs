@@ -1,15 +1,47 @@
 //! JSON conversions for Value.
 
 use std::convert::From;
+use std::iter;
 
 use conv::TryFrom;
 use conv::errors::GeneralError;
-use rustc_serialize::json::{Json, ToJson};
+use rustc_serialize::json::{Json, JsonEvent, Parser, StackElement, ToJson};
 
-use super::types::IntegerRepr;
+use eval::{self, Error};
+use eval::api::objects::set_attr;
+use super::types::{ArrayRepr, IntegerRepr, ObjectRepr};
 use super::Value;
 
 
+impl Value {
+    /// Parse JSON text into a value.
+    ///
+    /// Unlike going through rustc_serialize's Json type, this preserves
+    /// the order of keys in JSON objects.
+    pub fn from_json_str(input: &str) -> eval::Result {
+        let mut parser = Parser::new(input.chars());
+        let first = parser.next();
+        let result = try!(parse_json_value(&mut parser, first));
+        match parser.next() {
+            None => Ok(result),
+            Some(JsonEvent::Error(e)) => Err(Error::new(&format!("{}", e))),
+            Some(event) => Err(Error::new(&format!("unexpected JSON token: {:?}", event))),
+        }
+    }
+
+    /// Format the value as JSON text.
+    ///
+    /// Keys of objects are emitted in their insertion order.
+    /// If indentation is given, the output is pretty-printed
+    /// with that many spaces per nesting level.
+    pub fn to_json_string(&self, indent: Option<usize>) -> String {
+        let mut result = String::new();
+        write_json(&mut result, self, indent, 0);
+        result
+    }
+}
+
+
 impl TryFrom<Json> for Value {
     type Err = GeneralError<String>;
 
@@ -60,6 +92,7 @@ impl ToJson for Value {
             Value::Float(f) => Json::F64(f),
             Value::String(ref s) => Json::String(s.clone()),
             Value::Regex(ref r) => Json::String(r.as_str().to_owned()),
+            Value::Time(ref t) => Json::String(t.to_rfc3339()),
             Value::Array(ref a) => Json::Array(
                 a.iter().map(|v| v.to_json()).collect()
             ),
@@ -70,3 +103,91 @@ impl ToJson for Value {
         }
     }
 }
+
+
+// Utility functions
+
+fn parse_json_value<T>(parser: &mut Parser<T>, event: Option<JsonEvent>) -> eval::Result
+    where T: Iterator<Item=char>
+{
+    match event {
+        Some(JsonEvent::NullValue) => Ok(Value::Empty),
+        Some(JsonEvent::BooleanValue(b)) => Ok(Value::Boolean(b)),
+        Some(JsonEvent::I64Value(i)) => Ok(Value::Integer(i)),
+        Some(JsonEvent::U64Value(u)) => {
+            if u > (IntegerRepr::max_value() as u64) {
+                return Err(Error::new(&format!("JSON integer too large: {}", u)));
+            }
+            Ok(Value::Integer(u as IntegerRepr))
+        },
+        Some(JsonEvent::F64Value(f)) => Ok(Value::Float(f)),
+        Some(JsonEvent::StringValue(s)) => Ok(Value::String(s)),
+        Some(JsonEvent::ArrayStart) => {
+            let mut array = ArrayRepr::new();
+            loop {
+                match parser.next() {
+                    Some(JsonEvent::ArrayEnd) => return Ok(Value::Array(array)),
+                    event => array.push(try!(parse_json_value(parser, event))),
+                }
+            }
+        },
+        Some(JsonEvent::ObjectStart) => {
+            let mut object = ObjectRepr::new();
+            loop {
+                let event = parser.next();
+                match event {
+                    Some(JsonEvent::ObjectEnd) => return Ok(Value::Object(object)),
+                    Some(JsonEvent::Error(e)) => return Err(Error::new(&format!("{}", e))),
+                    _ => {},
+                }
+                // when the parser emits an attribute's value,
+                // the attribute's key is on top of its stack
+                let key = match parser.stack().top() {
+                    Some(StackElement::Key(k)) => k.to_owned(),
+                    _ => return Err(Error::new("invalid JSON object")),
+                };
+                let value = try!(parse_json_value(parser, event));
+                set_attr(&mut object, key, value);
+            }
+        },
+        Some(JsonEvent::Error(e)) => Err(Error::new(&format!("{}", e))),
+        Some(event) => Err(Error::new(&format!("unexpected JSON token: {:?}", event))),
+        None => Err(Error::new("unexpected end of JSON input")),
+    }
+}
+
+fn write_json(out: &mut String, value: &Value, indent: Option<usize>, level: usize) {
+    match *value {
+        Value::Array(ref a) if !a.is_empty() => {
+            out.push('[');
+            for (i, item) in a.iter().enumerate() {
+                if i > 0 { out.push(','); }
+                write_newline(out, indent, level + 1);
+                write_json(out, item, indent, level + 1);
+            }
+            write_newline(out, indent, level);
+            out.push(']');
+        },
+        Value::Object(ref o) if !o.is_empty() => {
+            out.push('{');
+            for (i, (k, v)) in o.iter().enumerate() {
+                if i > 0 { out.push(','); }
+                write_newline(out, indent, level + 1);
+                out.push_str(&Json::String(k.clone()).to_string());
+                out.push(':');
+                if indent.is_some() { out.push(' '); }
+                write_json(out, v, indent, level + 1);
+            }
+            write_newline(out, indent, level);
+            out.push('}');
+        },
+        _ => out.push_str(&value.to_json().to_string()),
+    }
+}
+
+fn write_newline(out: &mut String, indent: Option<usize>, level: usize) {
+    if let Some(indent) = indent {
+        out.push('\n');
+        out.extend(iter::repeat(' ').take(indent * level));
+    }
+}
@@ -18,6 +18,7 @@ use std::num::FpCategory;
 
 use conv::misc::InvalidSentinel;
 
+pub use self::conv::{Coercion, parse_int, parse_int_radix};
 pub use self::types::*;
 
 
@@ -45,6 +46,7 @@ pub enum Value {
     Float(FloatRepr),
     String(StringRepr),
     Regex(RegexRepr),
+    Time(TimeRepr),
     Array(ArrayRepr),
     Object(ObjectRepr),
     Function(FunctionRepr),
@@ -62,6 +64,7 @@ impl Value {
             Value::Float(..) => "float",
             Value::String(..) => "string",
             Value::Regex(..) => "regex",
+            Value::Time(..) => "time",
             Value::Array(..) => "array",
             Value::Object(..) => "object",
             Value::Function(..) => "function",
@@ -92,6 +95,7 @@ impl fmt::Debug for Value {
             },
             Value::String(ref s) => write!(fmt, "\"{}\"", s),
             Value::Regex(ref r) => write!(fmt, "/{}/", r.as_str()),
+            Value::Time(ref t) => write!(fmt, "@{}", t.to_rfc3339()),
             Value::Array(ref a) => {
                 write!(fmt, "[{}]", a.iter()
                     .map(|v| format!("{:?}", v)).collect::<Vec<String>>()
@@ -9,7 +9,6 @@ use std::fmt;
 
 use conv::TryFrom;
 use conv::errors::GeneralError;
-use rustc_serialize::json::ToJson;
 
 use super::Value;
 
@@ -49,13 +48,14 @@ impl<'a> TryFrom<&'a Value> for String {
             Value::Regex(..) => Err(GeneralError::Unrepresentable(
                 "cannot serialize a regex"
             )),
+            Value::Time(ref t) => Ok(t.to_rfc3339()),
             Value::Array(ref a) => {
                 // for final display, an array is assumed to contain lines of output
                 Ok(format!("{}", a.iter()
                     .map(|v| format!("{}", v)).collect::<Vec<String>>()
                     .join("\n")))
             },
-            Value::Object(..) => Ok(src.to_json().to_string()),
+            Value::Object(..) => Ok(src.to_json_string(None)),
             Value::Function(..) => Err(GeneralError::Unrepresentable(
                 "cannot serialize a function"
             )),
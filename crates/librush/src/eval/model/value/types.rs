@@ -3,8 +3,8 @@
 //! Note that "type" is not a first-class concept in the language.
 //! The various Value types are just a set of predefined enum variants.
 
-use std::collections::HashMap;
-
+use chrono::{DateTime, FixedOffset};
+use linked_hash_map::LinkedHashMap;
 use regex::Regex;
 
 use eval::model::Function;
@@ -18,8 +18,9 @@ pub type IntegerRepr = i64;
 pub type FloatRepr = f64;
 pub type StringRepr = String;
 pub type RegexRepr = Regex;
+pub type TimeRepr = DateTime<FixedOffset>;
 pub type ArrayRepr = Vec<Value>;
-pub type ObjectRepr = HashMap<String, Value>;
+pub type ObjectRepr = LinkedHashMap<String, Value>;
 pub type FunctionRepr = Function;
 
 
@@ -76,6 +77,7 @@ impl_value_type!(Integer(IntegerRepr)   => (is_integer,  unwrap_integer,  as_int
 impl_value_type!(Float(FloatRepr)       => (is_float,    unwrap_float,    as_float,    as_mut_float));
 impl_value_type!(String(StringRepr)     => (is_string,   unwrap_string,   as_string,   as_mut_string));
 impl_value_type!(Regex(RegexRepr)       => (is_regex,    unwrap_regex,    as_regex,    as_mut_regex));
+impl_value_type!(Time(TimeRepr)         => (is_time,     unwrap_time,     as_time,     as_mut_time));
 impl_value_type!(Array(ArrayRepr)       => (is_array,    unwrap_array,    as_array,    as_mut_array));
 impl_value_type!(Object(ObjectRepr)     => (is_object,   unwrap_object,   as_object,   as_mut_object));
 impl_value_type!(Function(FunctionRepr) => (is_function, unwrap_function, as_function, as_mut_function));
@@ -82,6 +82,7 @@ impl BinaryOpNode {
             "==" => BinaryOpNode::eval_eq(left, right),
             "!=" => BinaryOpNode::eval_ne(left, right),
             "@" => BinaryOpNode::eval_at(left, right),
+            "=~" => BinaryOpNode::eval_match(left, right),
             "&" => BinaryOpNode::eval_amp(left, right),
             "$" => BinaryOpNode::eval_dollar(left, right, &context),
             "+" => BinaryOpNode::eval_plus(left, right),
@@ -316,6 +317,15 @@ impl BinaryOpNode {
 
         BinaryOpNode::err("@", left, right)
     }
+
+    /// Evaluate the "=~" operator for two values.
+    fn eval_match(left: Value, right: Value) -> eval::Result {
+        // string =~ regex is the same as match(regex, string)
+        if left.is_string() && right.is_regex() {
+            return api::strings::match_(right, left);
+        }
+        BinaryOpNode::err("=~", left, right)
+    }
 }
 
 // Functional operators.
@@ -368,13 +378,19 @@ impl BinaryOpNode {
             left.append(&mut right);
             left
         }});
-        eval2!((left: &Object, right: &Object) -> Object {{
-            let mut left = left.clone();
-            for (k, v) in right {
-                left.insert(k.to_owned(), v.clone());
-            }
-            left
-        }});
+        if left.is_object() && right.is_object() {
+            return api::objects::merge(left, right);
+        }
+
+        // adding a number to time shifts it by that many seconds
+        if let (&Value::Time(ref t), true) = (&left, right.is_number()) {
+            let secs = try!(api::conv::float(right.clone())).unwrap_float();
+            return api::time::shift(t, secs);
+        }
+        if let (true, &Value::Time(ref t)) = (left.is_number(), &right) {
+            let secs = try!(api::conv::float(left.clone())).unwrap_float();
+            return api::time::shift(t, secs);
+        }
 
         BinaryOpNode::err("+", left, right)
     }
@@ -385,6 +401,17 @@ impl BinaryOpNode {
         eval2!(left, right : Float { left - right });
         eval2!((left: Integer, right: Float) -> Float { left as FloatRepr - right });
         eval2!((left: Float, right: Integer) -> Float { left - right as FloatRepr });
+
+        // subtracting times yields the number of seconds between them,
+        // while subtracting a number from time shifts it back by that many seconds
+        if let (&Value::Time(ref l), &Value::Time(ref r)) = (&left, &right) {
+            return Ok(api::time::difference(l, r));
+        }
+        if let (&Value::Time(ref t), true) = (&left, right.is_number()) {
+            let secs = try!(api::conv::float(right.clone())).unwrap_float();
+            return api::time::shift(t, -secs);
+        }
+
         BinaryOpNode::err("-", left, right)
     }
 
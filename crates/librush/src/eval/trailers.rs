@@ -5,6 +5,8 @@ use eval::{self, api, Context, Eval, Value};
 use eval::model::Invoke;
 use eval::model::value::{ArrayRepr, ObjectRepr, StringRepr};
 use parse::ast::{FunctionCallNode, Index, SubscriptNode};
+use eval::api::strings::text_units;
+use super::atoms::eval_key;
 
 
 /// Evaluate the function call AST node.
@@ -21,6 +23,17 @@ impl Eval for FunctionCallNode {
                 args.push(arg);
             }
 
+            // keyword arguments (if any) are bound to the function right away,
+            // so that positional arguments go to the remaining parameters
+            if !self.kwargs.is_empty() {
+                let mut kwargs = Vec::with_capacity(self.kwargs.len());
+                for &(ref name, ref arg) in &self.kwargs {
+                    let arg = try!(arg.eval(context));
+                    kwargs.push((name.clone(), arg));
+                }
+                f = try!(f.bind_kwargs(kwargs));
+            }
+
             // determine if it's a regular call or curry (partial application)
             if f.arity() > args.len() {
                 for arg in args.into_iter() {
@@ -53,7 +66,8 @@ impl Eval for SubscriptNode {
 impl SubscriptNode {
     fn eval_point(&self, index: &Box<Eval>, context: &mut Context) -> eval::Result {
         let object = try!(self.object.eval(context));
-        let index = try!(index.eval(context));
+        let index = if object.is_object() { try!(eval_key(index, context)) }
+                    else { try!(index.eval(context)) };
 
         // TODO(xion): roll this into eval_point_on_array(), which would require
         // copying parts of the filter() function implementation
@@ -62,7 +76,8 @@ impl SubscriptNode {
         }
 
         match object {
-            Value::String(ref s) => SubscriptNode::eval_point_on_string(s, index),
+            Value::String(ref s) =>
+                SubscriptNode::eval_point_on_string(s, index, context.uses_graphemes()),
             Value::Array(ref a) => SubscriptNode::eval_point_on_array(a, index),
             Value::Object(ref o) => SubscriptNode::eval_point_on_object(o, index),
             _ => Err(eval::Error::new(
@@ -82,7 +97,8 @@ impl SubscriptNode {
                     else { None };
 
         match object {
-            Value::String(ref s) => SubscriptNode::eval_range_on_string(s, left, right),
+            Value::String(ref s) =>
+                SubscriptNode::eval_range_on_string(s, left, right, context.uses_graphemes()),
             Value::Array(ref a) => SubscriptNode::eval_range_on_array(a, left, right),
             _ => Err(eval::Error::new(
                 &format!("can't index a(n) {} with range of {} and {}",
@@ -96,15 +112,11 @@ impl SubscriptNode {
 
 // Evaluation of point indices against various value types.
 impl SubscriptNode {
-    fn eval_point_on_string(string: &StringRepr, index: Value) -> eval::Result {
+    fn eval_point_on_string(string: &StringRepr, index: Value, graphemes: bool) -> eval::Result {
+        let units = text_units(string, graphemes);
         SubscriptNode::extract_string_index(index)
-            .and_then(|i| SubscriptNode::resolve_index(i, string.len()))
-            .map(|i| {
-                let c = string.chars().nth(i).unwrap();
-                let mut result = String::new();
-                result.push(c);
-                Value::String(result)
-            })
+            .and_then(|i| SubscriptNode::resolve_index(i, units.len()))
+            .map(|i| Value::String(units[i].to_owned()))
     }
 
     fn eval_point_on_array(array: &ArrayRepr, index: Value) -> eval::Result {
@@ -136,7 +148,8 @@ impl SubscriptNode {
 // Evaluation of range indices against various value types.
 impl SubscriptNode {
     fn eval_range_on_string(string: &StringRepr,
-                            left: Option<Value>, right: Option<Value>) -> eval::Result {
+                            left: Option<Value>, right: Option<Value>,
+                            graphemes: bool) -> eval::Result {
         // special case for the full range since we can deal with it quickly
         if left.is_none() && right.is_none() {
             return Ok(Value::String(string.clone()));
@@ -144,22 +157,19 @@ impl SubscriptNode {
 
         // turn the range with potentially unspecified ends into
         // fully specified range using the string's length as a limit
+        let units = text_units(string, graphemes);
         let resolve_index = |idx| {
             SubscriptNode::extract_string_index(idx)
-                .and_then(|i| SubscriptNode::resolve_index(i, string.len()))
+                .and_then(|i| SubscriptNode::resolve_index(i, units.len()))
         };
         let left = if let Some(left) = left { try!(resolve_index(left)) }
                    else { 0 };
         let right = if let Some(right) = right { try!(resolve_index(right)) }
-                    else { string.len() };
+                    else { units.len() };
 
         // copy the character range into the resulting string
         let len = if left < right { right - left } else { 0 };
-        let mut result = String::with_capacity(len);
-        for ch in string.chars().skip(left).take(len) {
-            result.push(ch);
-        }
-        Ok(Value::String(result))
+        Ok(Value::String(units.iter().skip(left).take(len).cloned().collect()))
     }
 
     fn eval_range_on_array(array: &ArrayRepr,
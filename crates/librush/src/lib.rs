@@ -7,17 +7,27 @@
 #[macro_use] extern crate nom;
 #[macro_use] extern crate log;
 
+             extern crate chrono;
+             extern crate chrono_tz;
              extern crate conv;
+             extern crate crc;
              extern crate csv;
              extern crate fnv;
+             extern crate hmac;
 #[macro_use] extern crate lazy_static;
+             extern crate linked_hash_map;
+             extern crate md5;
 #[macro_use] extern crate mopa;
              extern crate rand;
              extern crate regex;
              extern crate rustc_serialize;
+             extern crate sha1;
+             extern crate sha2;
+             extern crate twox_hash;
              extern crate unicode_categories;
              extern crate unicode_normalization;
              extern crate unicode_segmentation;
+             extern crate unicode_width;
              extern crate unidecode;
 
 
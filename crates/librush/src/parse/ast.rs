@@ -4,6 +4,7 @@
 //! For the code that evaluates those nodes, see the `eval` module.
 
 use std::fmt;
+use std::rc::Rc;
 use std::str::FromStr;
 
 use eval::{Eval, Value};
@@ -86,6 +87,84 @@ impl fmt::Debug for ObjectNode {
 }
 
 
+/// Parameters of a user-defined function (lambda or `def`).
+pub struct Params {
+    /// Names of regular parameters,
+    /// together with the expressions for their default values (if any).
+    pub names: Vec<(String, Option<Box<Eval>>)>,
+    /// Name of the parameter which gathers any remaining arguments into an array.
+    pub rest: Option<String>,
+}
+
+impl Params {
+    #[inline]
+    pub fn new(names: Vec<(String, Option<Box<Eval>>)>, rest: Option<String>) -> Params {
+        Params{names: names, rest: rest}
+    }
+}
+
+impl fmt::Debug for Params {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut params: Vec<_> = self.names.iter()
+            .map(|&(ref name, ref default)| match *default {
+                Some(ref d) => format!("{}={:?}", name, d),
+                None => name.clone(),
+            })
+            .collect();
+        if let Some(ref rest) = self.rest {
+            params.push(format!("*{}", rest));
+        }
+        write!(fmt, "{}", params.join(","))
+    }
+}
+
+
+/// AST node representing a lambda expression, i.e. an anonymous function.
+///
+/// Evaluating it creates a closure that captures the current scope.
+pub struct LambdaNode {
+    pub params: Params,
+    pub body: Rc<Box<Eval>>,
+}
+
+impl LambdaNode {
+    #[inline]
+    pub fn new(params: Params, body: Box<Eval>) -> LambdaNode {
+        LambdaNode{params: params, body: Rc::new(body)}
+    }
+}
+
+impl fmt::Debug for LambdaNode {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "<Lambda: |{:?}| {:?}>", self.params, self.body)
+    }
+}
+
+
+/// AST node representing a definition of a named function.
+///
+/// Evaluating it creates a closure (similarly to LambdaNode)
+/// and assigns it to the function's name.
+pub struct DefinitionNode {
+    pub name: String,
+    pub params: Params,
+    pub body: Rc<Box<Eval>>,
+}
+
+impl DefinitionNode {
+    #[inline]
+    pub fn new(name: String, params: Params, body: Box<Eval>) -> DefinitionNode {
+        DefinitionNode{name: name, params: params, body: Rc::new(body)}
+    }
+}
+
+impl fmt::Debug for DefinitionNode {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "<Def: {}({:?}) = {:?}>", self.name, self.params, self.body)
+    }
+}
+
+
 /// AST node repreenting an operation involving a unary operator and its argument.
 pub struct UnaryOpNode {
     pub op: String,
@@ -238,12 +317,19 @@ impl fmt::Debug for SubscriptNode {
 pub struct FunctionCallNode {
     pub func: Box<Eval>,
     pub args: Vec<Box<Eval>>,
+    pub kwargs: Vec<(String, Box<Eval>)>,
 }
 
 impl FunctionCallNode {
     #[inline]
     pub fn new(func: Box<Eval>, args: Vec<Box<Eval>>) -> FunctionCallNode {
-        FunctionCallNode{func: func, args: args}
+        FunctionCallNode::with_kwargs(func, args, vec![])
+    }
+
+    #[inline]
+    pub fn with_kwargs(func: Box<Eval>, args: Vec<Box<Eval>>,
+                       kwargs: Vec<(String, Box<Eval>)>) -> FunctionCallNode {
+        FunctionCallNode{func: func, args: args, kwargs: kwargs}
     }
 }
 
@@ -251,6 +337,7 @@ impl fmt::Debug for FunctionCallNode {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let args = self.args.iter()
             .map(|arg| format!("{:?}", arg))
+            .chain(self.kwargs.iter().map(|&(ref name, ref arg)| format!("{}={:?}", name, arg)))
             .collect::<Vec<String>>().join(",");
         write!(fmt, "<Call: {:?}({})>", self.func, args)
     }
@@ -3,28 +3,35 @@
 use std::f64;
 use std::str::from_utf8;
 
-use nom::{self, alpha, alphanumeric, IResult};
-use regex::Regex;
+use nom::{self, alpha, alphanumeric, IResult, Needed};
+use regex::{Regex, RegexBuilder};
 
 use eval::{Eval, Value};
-use eval::value::{FloatRepr, IntegerRepr, RegexRepr, StringRepr};
+use eval::value::{parse_int, FloatRepr, RegexRepr, StringRepr};
 use parse::ast::{ArrayNode, ObjectNode, ScalarNode};
 use super::structure::expression;
 
 
 // TODO(xion): switch from parsers expecting &[u8] to accepting &str;
-// this will get rid of the hack in float_literal() and possibly other cruft
+// this will get rid of the hack in number_literal() and possibly other cruft
 
 
 const RESERVED_WORDS: &'static [&'static str] = &[
-    "const", "do", "else", "false", "for", "if", "let", "true", "while",
+    "const", "def", "do", "else", "false", "for", "if", "let", "true", "while",
 ];
 
-const DIGITS: &'static str = "0123456789";
-const FLOAT_REGEX: &'static str = r"(0|[1-9][0-9]*)\.[0-9]+([eE][+-]?[1-9][0-9]*)?";
+const INT_REGEX: &'static str = concat!(
+    r"0[xX][0-9a-fA-F](_?[0-9a-fA-F])*|0[oO][0-7](_?[0-7])*|0[bB][01](_?[01])*|",
+    r"[1-9](_?[0-9])*|0"
+);
+const FLOAT_REGEX: &'static str = concat!(
+    r"((0|[1-9](_?[0-9])*)\.[0-9](_?[0-9])*|\.[0-9](_?[0-9])*)([eE][+-]?[0-9]+)?|",
+    r"(0|[1-9](_?[0-9])*)[eE][+-]?[0-9]+"
+);
 const ESCAPE: &'static str = "\\";
+const REGEX_FLAGS: &'static str = "imsx";
 
-const UNDERSCORE_SUFFIXES: &'static str = "bifs";
+const UNDERSCORE_SUFFIXES: &'static str = "bfilnops";
 
 
 /// identifier ::== ('_' SUFFIX?) | (ALPHA ALPHANUMERIC*)
@@ -51,11 +58,12 @@ named!(pub atom( &[u8] ) -> Box<Eval>, alt!(
     // Note that order of those branches matters.
     // Literals that have special case'd identifiers as valid values
     // -- like floats with their NaN -- have to be before symbols!
+    // The same applies to regexes with alternative delimiters, like r"..." or m{...}.
     //
     nil_value |
     object_value | array_value |
-    bool_value | float_value | int_value | symbol_value |
-    regex_value | string_value |
+    bool_value | float_value | int_value | regex_value | symbol_value |
+    string_value |
     delimited!(multispaced!(tag!("(")), expression, multispaced!(tag!(")")))
 ));
 
@@ -95,26 +103,33 @@ named!(symbol_value( &[u8] ) -> Box<Eval>, map!(identifier, |value: String| {
     Box::new(ScalarNode::from(Value::Symbol(value)))
 }));
 
+/// INTEGER ::== DECIMAL | ('0x' HEX_DIGITS) | ('0o' OCT_DIGITS) | ('0b' BIN_DIGITS)
+/// (digits can be separated with single underscores, as in 1_000_000)
 named!(int_value( &[u8] ) -> Box<Eval>, map_res!(int_literal, |value: String| {
-    value.parse::<IntegerRepr>().map(ScalarNode::from).map(Box::new)
+    parse_int(&value).map(ScalarNode::from).map(Box::new).ok_or(())
 }));
-named!(int_literal( &[u8] ) -> String, string!(alt!(
-    seq!(char_of!(&DIGITS[1..]), many0!(char_of!(DIGITS))) | tag!("0")
-)));
+fn int_literal(input: &[u8]) -> IResult<&[u8], String> {
+    number_literal(input, INT_REGEX)
+}
 
 named!(float_value( &[u8] ) -> Box<Eval>, alt!(
     tag!("Inf") => { |_| Box::new(ScalarNode::from(f64::INFINITY as FloatRepr)) } |
     tag!("NaN") => { |_| Box::new(ScalarNode::from(f64::NAN as FloatRepr)) } |
     map_res!(float_literal, |value: String| {
-        value.parse::<FloatRepr>().map(ScalarNode::from).map(Box::new)
+        value.replace("_", "").parse::<FloatRepr>().map(ScalarNode::from).map(Box::new)
     })
 ));
 fn float_literal(input: &[u8]) -> IResult<&[u8], String> {
+    number_literal(input, FLOAT_REGEX)
+}
+
+/// Parse a numeric literal that matches given regular expression.
+fn number_literal<'i>(input: &'i [u8], regex: &str) -> IResult<&'i [u8], String> {
     let (_, input) = try_parse!(input, expr_res!(from_utf8(input)));
 
     // TODO(xion): use *_static! variant when regexp_macros feature
     // can be used in stable Rust
-    let regex = "^".to_owned() + FLOAT_REGEX;  // 'coz we want immediate match
+    let regex = format!("^(?:{})", regex);  // 'coz we want immediate match
     let result = re_find!(input, &regex);
 
     // This match has to be explicit (rather than try_parse! etc.)
@@ -128,14 +143,36 @@ fn float_literal(input: &[u8]) -> IResult<&[u8], String> {
             IResult::Done(rest.as_bytes(), String::from(parsed)),
         IResult::Incomplete(i) => IResult::Incomplete(i),
         IResult::Error(nom::Err::Code(e)) => IResult::Error(nom::Err::Code(e)),
-        r => unreachable!("unexpected result from parsing float: {:?}", r),
+        r => unreachable!("unexpected result from parsing number: {:?}", r),
     }
 }
 
 named!(regex_value( &[u8] ) -> Box<Eval>, map!(regex_literal, |value: RegexRepr| {
     Box::new(ScalarNode::from(value))
 }));
+/// REGEX ::== (SLASHED_REGEX | QUOTED_REGEX | BRACED_REGEX) REGEX_FLAG*
 fn regex_literal(input: &[u8]) -> IResult<&[u8], Regex> {
+    let (input, pattern) = try_parse!(input, alt!(
+        slashed_regex | quoted_regex | braced_regex
+    ));
+
+    // flags (if any) immediately follow the closing delimiter
+    let flags_len = input.iter()
+        .take_while(|b| REGEX_FLAGS.as_bytes().contains(b))
+        .count();
+    let (flags, input) = (&input[..flags_len], &input[flags_len..]);
+    let (_, flags) = try_parse!(input, expr_res!(from_utf8(flags)));
+
+    expr_res!(input, RegexBuilder::new(&pattern)
+        .case_insensitive(flags.contains('i'))
+        .multi_line(flags.contains('m'))
+        .dot_matches_new_line(flags.contains('s'))
+        .ignore_whitespace(flags.contains('x'))
+        .compile())
+}
+
+/// SLASHED_REGEX ::== '/' CHARS '/'
+fn slashed_regex(input: &[u8]) -> IResult<&[u8], String> {
     let (mut input, _) = try_parse!(input, tag!("/"));
 
     // consume chacters until the closing slash
@@ -144,27 +181,79 @@ fn regex_literal(input: &[u8]) -> IResult<&[u8], Regex> {
         let (rest, chunk) = try_parse!(input,
                                        string!(take_until_and_consume!("/")));
         r.push_str(&chunk);
-
         input = rest;
+
+        // slash can be escaped with a backslash to make it part of the regex
+        if ends_with_escape(&chunk) {
+            r.pop();
+            r.push('/');
+            continue;
+        }
         if input.is_empty() {
             break;
         }
 
         // Try to parse what we've got so far as a regex;
         // if it succeeds, then this is our result.
-        // Note that this will handle escaping of the slash
-        // (to make it literal part of the regex) through character class [/],
+        // Note that this will also handle the slash inside a character class [/],
         // since unterminated square bracket won't parse as regex.
-        if let Ok(regex) = Regex::new(&r) {
-            return IResult::Done(input, regex);
+        if Regex::new(&r).is_ok() {
+            return IResult::Done(input, r);
         }
 
         r.push('/');
     }
 
     // If we exhausted the input, then whatever we've accumulated so far
-    // may still be a valid regex, so try to parse it.
-    expr_res!(input, Regex::new(&r))
+    // may still be a valid regex, so let the caller try to compile it.
+    IResult::Done(input, r)
+}
+
+/// QUOTED_REGEX ::== 'r"' CHARS '"'
+fn quoted_regex(input: &[u8]) -> IResult<&[u8], String> {
+    let (mut input, _) = try_parse!(input, complete!(tag!("r\"")));
+
+    // Unlike in regular strings, backslashes are left intact
+    // except when they're escaping the double quote.
+    let mut r = String::new();
+    loop {
+        let (rest, chunk) = try_parse!(input,
+                                       string!(take_until_and_consume!("\"")));
+        r.push_str(&chunk);
+        input = rest;
+
+        if !ends_with_escape(&chunk) {
+            break;
+        }
+        r.pop();
+        r.push('"');
+    }
+
+    IResult::Done(input, r)
+}
+
+/// BRACED_REGEX ::== 'm{' CHARS '}'
+fn braced_regex(input: &[u8]) -> IResult<&[u8], String> {
+    let (input, _) = try_parse!(input, complete!(tag!("m{")));
+
+    // Braces inside the regex (like in the {m,n} repetition)
+    // are fine as long as they're balanced or escaped.
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, &b) in input.iter().enumerate() {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'{' => depth += 1,
+            b'}' if depth > 0 => depth -= 1,
+            b'}' => {
+                let (_, r) = try_parse!(input, expr_res!(from_utf8(&input[..i])));
+                return IResult::Done(&input[i + 1..], String::from(r));
+            },
+            _ => {},
+        }
+    }
+    IResult::Incomplete(Needed::Unknown)
 }
 
 named!(string_value( &[u8] ) -> Box<Eval>, map!(string_literal, |value: StringRepr| {
@@ -203,3 +292,13 @@ fn string_literal(input: &[u8]) -> IResult<&[u8], String> {
 
     IResult::Done(input, s)
 }
+
+
+// Utility functions
+
+/// Check whether given chunk of a literal ends with an escape character
+/// that isn't itself escaped.
+fn ends_with_escape(chunk: &str) -> bool {
+    let escapes = chunk.chars().rev().take_while(|c| ESCAPE.starts_with(*c)).count();
+    escapes % 2 == 1
+}
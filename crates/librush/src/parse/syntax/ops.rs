@@ -24,7 +24,7 @@ named!(pub logical_op( &[u8] ) -> String, string!(multispaced!(alt_complete!(
     tag!("&&") | tag!("||")
 ))));
 named!(pub comparison_op( &[u8] ) -> String, string!(multispaced!(alt_complete!(
-    tag!("<=") | tag!(">=") | tag!("==") | tag!("!=") | char_of!("<>@")
+    tag!("<=") | tag!(">=") | tag!("==") | tag!("!=") | tag!("=~") | char_of!("<>@")
 ))));
 named!(pub additive_op( &[u8] ) -> String, string!(multispaced!(
     char_of!("+-")
@@ -1,13 +1,16 @@
 //! Module defining grammar symbols that form the main structure of the syntax.
 
-use eval::{Eval, Function};
+use nom::multispace;
+
+use eval::Eval;
 use parse::ast::*;
 use super::literals::{atom, identifier};
 use super::ops::*;
 
 
 /// Root symbol of the grammar.
-named!(pub expression( &[u8] ) -> Box<Eval>, chain!(e: assignment, || { e }));
+/// expression ::== definition | assignment
+named!(pub expression( &[u8] ) -> Box<Eval>, alt!(definition | assignment));
 
 
 /// Macros shortening the repetitive parts of defining syntactical constructs
@@ -38,6 +41,19 @@ macro_rules! right_assoc (
 );
 
 
+/// definition ::== 'def' IDENTIFIER '(' PARAMS ')' '=' expression
+named!(definition( &[u8] ) -> Box<Eval>, chain!(
+    opt!(multispace) ~
+    complete!(tag!("def")) ~ multispace ~
+    name: identifier ~
+    multispaced!(tag!("(")) ~
+    params: params ~
+    multispaced!(tag!(")")) ~
+    multispaced!(tag!("=")) ~
+    body: expression,
+    move || { Box::new(DefinitionNode::new(name, params, body)) as Box<Eval> }
+));
+
 /// assignment ::== functional (ASSIGNMENT_OP functional)*
 right_assoc!(assignment => functional (assignment_op functional)*);
 
@@ -47,15 +63,52 @@ left_assoc!(functional => joint (functional_op joint)*);
 /// joint ::== conditional | lambda | curried_op
 named!(joint( &[u8] ) -> Box<Eval>, alt!(conditional | lambda | curried_op));
 
-/// lambda ::== '|' ARGS '|' joint
+/// lambda ::== '|' PARAMS '|' joint
 named!(lambda( &[u8] ) -> Box<Eval>, chain!(
     multispaced!(tag!("|")) ~
-    args: separated_list!(multispaced!(tag!(",")), identifier) ~
+    params: params ~
     multispaced!(tag!("|")) ~
     body: joint,
-    move || { Box::new(
-        ScalarNode::from(Function::from_lambda(args, body))
-    ) as Box<Eval> }
+    move || { Box::new(LambdaNode::new(params, body)) as Box<Eval> }
+));
+
+/// PARAMS ::== [PARAM (',' PARAM)*]
+/// PARAM ::== IDENTIFIER ['=' power] | '*' IDENTIFIER
+///
+/// Parameters with default values have to follow those without,
+/// and the '*' parameter (if any) has to be the last one.
+enum Param { Regular(String, Option<Box<Eval>>), Rest(String) }
+named!(params( &[u8] ) -> Params, map_res!(
+    separated_list!(multispaced!(tag!(",")), alt!(
+        chain!(
+            multispaced!(tag!("*")) ~
+            name: identifier,
+            move || { Param::Rest(name) }
+        ) |
+        chain!(
+            name: identifier ~
+            default: maybe!(chain!(multispaced!(tag!("=")) ~ d: power, move || { d })),
+            move || { Param::Regular(name, default) }
+        )
+    )),
+    |params: Vec<Param>| {
+        let mut names: Vec<(String, Option<Box<Eval>>)> = Vec::with_capacity(params.len());
+        let mut rest = None;
+        for param in params {
+            match param {
+                _ if rest.is_some() => return Err(()),
+                Param::Rest(name) => rest = Some(name),
+                Param::Regular(name, default) => {
+                    let after_default = names.last().map_or(false, |&(_, ref d)| d.is_some());
+                    if after_default && default.is_none() {
+                        return Err(());
+                    }
+                    names.push((name, default));
+                },
+            }
+        }
+        Ok(Params::new(names, rest))
+    }
 ));
 
 /// curried_op ::== '(' (atom BINARY_OP) | (BINARY_OP atom) | BINARY_OP ')'
@@ -139,8 +192,8 @@ named!(power( &[u8] ) -> Box<Eval>, chain!(
             result = match trailer {
                 Trailer::Subscript(index) =>
                     Box::new(SubscriptNode::new(result, index)),
-                Trailer::Args(args) =>
-                    Box::new(FunctionCallNode::new(result, args)),
+                Trailer::Args(args, kwargs) =>
+                    Box::new(FunctionCallNode::with_kwargs(result, args, kwargs)),
             };
         }
 
@@ -156,15 +209,44 @@ named!(power( &[u8] ) -> Box<Eval>, chain!(
 ));
 
 /// trailer ::== '[' INDEX ']' | '(' ARGS ')'
-enum Trailer { Subscript(Index), Args(Vec<Box<Eval>>) }
+enum Trailer { Subscript(Index), Args(Vec<Box<Eval>>, Vec<(String, Box<Eval>)>) }
 named!(trailer( &[u8] ) -> Trailer, alt!(
     delimited!(multispaced!(tag!("[")),
                index,
                multispaced!(tag!("]"))) => { |idx| Trailer::Subscript(idx) }
     |
     delimited!(multispaced!(tag!("(")),
-               separated_list!(multispaced!(tag!(",")), expression),
-               multispaced!(tag!(")"))) => { |args| Trailer::Args(args) }
+               args,
+               multispaced!(tag!(")"))) => { |(args, kwargs)| Trailer::Args(args, kwargs) }
+));
+
+/// ARGS ::== [ARG (',' ARG)*]
+/// ARG ::== expression | IDENTIFIER '=' expression
+///
+/// Keyword arguments (IDENTIFIER '=' expression) have to follow the positional ones.
+enum Arg { Positional(Box<Eval>), Keyword(String, Box<Eval>) }
+named!(args( &[u8] ) -> (Vec<Box<Eval>>, Vec<(String, Box<Eval>)>), map_res!(
+    separated_list!(multispaced!(tag!(",")), alt!(
+        chain!(
+            name: identifier ~
+            multispaced!(tag!("=")) ~
+            value: expression,
+            move || { Arg::Keyword(name, value) }
+        ) |
+        expression => { |expr| Arg::Positional(expr) }
+    )),
+    |args: Vec<Arg>| {
+        let mut positional = vec![];
+        let mut keyword = vec![];
+        for arg in args {
+            match arg {
+                Arg::Positional(_) if !keyword.is_empty() => return Err(()),
+                Arg::Positional(expr) => positional.push(expr),
+                Arg::Keyword(name, expr) => keyword.push((name, expr)),
+            }
+        }
+        Ok((positional, keyword))
+    }
 ));
 named!(index( &[u8] ) -> Index, alt!(
     chain!(
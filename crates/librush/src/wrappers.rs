@@ -5,9 +5,9 @@ use std::io::{self, Read, Write, BufRead, BufReader, BufWriter};
 use std::u8;
 
 use conv::TryFrom;
-use conv::misc::InvalidSentinel;
 
 use super::eval::{Error as EvalError, Eval, Context, Invoke, Result as EvalResult, Value};
+use super::eval::{RAW_INPUT, truthy};
 use super::eval::value::IntegerRepr;
 use super::parse::parse;
 
@@ -15,6 +15,15 @@ use super::parse::parse;
 /// Name of the variable within expression context that holds the current/input value.
 const CURRENT: &'static str = "_";
 
+/// Names of the variables holding metadata about the current input record.
+const RECORD_INDEX: &'static str = "_n";
+const LINE_NUMBER: &'static str = "_l";
+const BYTE_OFFSET: &'static str = "_o";
+
+/// Name of the variable holding the path of the file that the input comes from.
+/// Callers which process multiple files should set it before processing each one.
+pub const FILE_PATH: &'static str = "_p";
+
 
 /// Evaluate the expression within given Context.
 /// Returns the resulting Value.
@@ -56,6 +65,13 @@ pub fn map_lines<R: Read, W: Write>(expr: &str, input: R, output: &mut W) -> io:
     map_lines_multi(&[expr], input, output)
 }
 
+/// Filter the given input stream line by line, using the expression as predicate.
+/// Lines for which it is truthy are written unchanged to the given output stream.
+#[inline]
+pub fn filter_lines<R: Read, W: Write>(expr: &str, input: R, output: &mut W) -> io::Result<()> {
+    filter_lines_multi(&[expr], input, output)
+}
+
 /// Apply the expression to given input stream, word by word,
 /// (each word treated as string in the expression itself),
 /// and writing to the given output stream.
@@ -75,7 +91,8 @@ pub fn map_chars<R: Read, W: Write>(expr: &str, input: R, output: &mut W) -> io:
 /// Apply the expression to bytes of given input stream,
 /// writing the transformed bytes into given output stream.
 ///
-/// Note that the expression must always produce a byte (i.e. an integer from the 0-255 range).
+/// Note that the expression must always produce a byte (i.e. an integer from the 0-255 range),
+/// or nil to skip the input byte.
 #[inline]
 pub fn map_bytes<R: Read, W: Write>(expr: &str, input: R, output: &mut W) -> io::Result<()> {
     map_bytes_multi(&[expr], input, output)
@@ -129,6 +146,19 @@ pub fn map_lines_multi<R: Read, W: Write>(exprs: &[&str], input: R, output: &mut
     map_lines_multi_ctx(&mut context, exprs, input, output)
 }
 
+/// Filter the input stream line by line through a sequence of expressions.
+///
+/// Every line read from the stream is fed to the first expression
+/// (without the \n char) whose result is then passed to the second one, etc.
+///
+/// If the final result is truthy, the original line is written to the given output stream.
+/// This continues for each line of input.
+#[inline]
+pub fn filter_lines_multi<R: Read, W: Write>(exprs: &[&str], input: R, output: &mut W) -> io::Result<()> {
+    let mut context = Context::new();
+    filter_lines_multi_ctx(&mut context, exprs, input, output)
+}
+
 /// Apply a sequence of expressions to the input stream, word by word.
 ///
 /// Every word read from the stream is fed to the first expression,
@@ -160,7 +190,7 @@ pub fn map_chars_multi<R: Read, W: Write>(exprs: &[&str], input: R, output: &mut
 /// Every byte read from the stream is fed to the first expression
 /// (as an integer from 0-255 range), whose result is then passed to the second one, etc.
 ///
-/// The final result -- which has to be a 0-255 integer -- is written then
+/// The final result -- which has to be a 0-255 integer, or nil -- is written then
 /// to the given output stream. This continues for each byte of input.
 #[inline]
 pub fn map_bytes_multi<R: Read, W: Write>(exprs: &[&str], input: R, output: &mut W) -> io::Result<()> {
@@ -205,10 +235,18 @@ pub fn apply_string_multi_ctx<R, W>(context: &mut Context,
     let byte_count = try!(reader.read_to_string(&mut input));
     let char_count = input.chars().count();
 
-    context.set(CURRENT, Value::String(input));
+    let input = Value::String(input);
+    set_input(context, input.clone(), input);
 
+    // When preserving line endings, a result that already ends with a newline
+    // (like the input itself, usually) shouldn't get another one.
+    let keep_endings = context.keeps_line_endings();
     let result = try!(process(context, &asts));
-    try!(write_result_line(output, result));
+    let terminator = match *result {
+        Value::String(ref s) if keep_endings && s.ends_with('\n') => "",
+        _ => "\n",
+    };
+    try!(write_result(output, result, terminator));
 
     info!("Processed {} character(s), or {} byte(s), through {} expression(s)",
           char_count, byte_count, expr_count);
@@ -222,7 +260,6 @@ pub fn apply_string_multi_ctx<R, W>(context: &mut Context,
 /// Expression context is shared throughout.
 ///
 /// The final result is written to the given output stream.
-#[allow(or_fun_call)]
 pub fn apply_lines_multi_ctx<R, W>(context: &mut Context,
                                    exprs: &[&str],
                                    input: R, output: &mut W) -> io::Result<()>
@@ -231,15 +268,16 @@ pub fn apply_lines_multi_ctx<R, W>(context: &mut Context,
     let asts = try!(parse_exprs(exprs));
     let expr_count = asts.len();
 
-    // parse input lines into a vector of Value objects
-    let lines: Vec<_> = BufReader::new(input).lines()
-        .map(|r| r.expect("failed to read input line")
-            .parse::<Value>().unwrap_or(Value::invalid_sentinel()))
-        .filter(|v| *v != Value::invalid_sentinel())
-        .collect();
+    // parse input lines into a vector of Value objects,
+    // retaining the raw strings for the typed views of input
+    let coercion = context.coercion();
+    let (lines, raw_lines): (Vec<_>, Vec<_>) = BufReader::new(input).lines()
+        .map(|r| r.expect("failed to read input line"))
+        .map(|line| (coercion.apply(line.clone()), Value::String(line)))
+        .unzip();
     let line_count = lines.len();
 
-    context.set(CURRENT, Value::Array(lines));
+    set_input(context, Value::Array(lines), Value::Array(raw_lines));
 
     let result = try!(process(context, &asts));
     try!(write_result_line(output, result));
@@ -265,22 +303,85 @@ pub fn map_lines_multi_ctx<R, W>(context: &mut Context,
     let asts = try!(parse_exprs(exprs));
     let expr_count = asts.len();
 
-    let reader = BufReader::new(input);
+    let mut reader = BufReader::new(input);
     let mut writer = BufWriter::new(output);
 
+    let first_index = next_record_index(context);
+    let keep_endings = context.keeps_line_endings();
     let mut line_count = 0;
-    for line in reader.lines() {
-        let line = try!(line);
-        context.set(CURRENT, to_value(line));
+    let mut offset = 0;
+    let mut buffer = String::new();
+    while try!(reader.read_line(&mut buffer)) > 0 {
+        {
+            let (line, terminator) = split_terminator(&buffer);
+            let coercion = context.coercion();
+            set_input(context, coercion.apply(line.to_owned()), Value::String(line.to_owned()));
+            set_position(context, first_index + line_count, line_count + 1, offset);
+
+            // Unless asked to reproduce the line terminators verbatim,
+            // every result is written as a separate line.
+            let terminator = if keep_endings { terminator } else { "\n" };
+            let result = try!(process(context, &asts));
+            try!(write_result(&mut writer, result, terminator));
+        }
+        offset += buffer.len();
+        buffer.clear();
+        line_count += 1;
+    }
 
-        let result = try!(process(context, &asts));
-        try!(write_result_line(&mut writer, result));
+    info!("Processed {} line(s) of input through {} expression(s)",
+          line_count, expr_count);
+    Ok(())
+}
 
+/// Filter the input stream line by line through a sequence of expressions.
+///
+/// Every line read from the stream is fed to the first expression
+/// (without the \n char) whose result is then passed to the second one, etc.
+/// Expression context is shared throughout.
+///
+/// If the final result is truthy, the original line is written to the given output stream.
+/// This continues for each line of input.
+pub fn filter_lines_multi_ctx<R, W>(context: &mut Context,
+                                    exprs: &[&str],
+                                    input: R, output: &mut W) -> io::Result<()>
+    where R: Read, W: Write
+{
+    let asts = try!(parse_exprs(exprs));
+    let expr_count = asts.len();
+
+    let mut reader = BufReader::new(input);
+    let mut writer = BufWriter::new(output);
+
+    let first_index = next_record_index(context);
+    let keep_endings = context.keeps_line_endings();
+    let mut line_count = 0;
+    let mut kept_count = 0;
+    let mut offset = 0;
+    let mut buffer = String::new();
+    while try!(reader.read_line(&mut buffer)) > 0 {
+        {
+            let (line, terminator) = split_terminator(&buffer);
+            let coercion = context.coercion();
+            set_input(context, coercion.apply(line.to_owned()), Value::String(line.to_owned()));
+            set_position(context, first_index + line_count, line_count + 1, offset);
+
+            let result = try!(process(context, &asts)).clone();
+            let keep = try!(truthy(result)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))).unwrap_bool();
+            if keep {
+                let terminator = if keep_endings { terminator } else { "\n" };
+                try!(write!(writer, "{}{}", line, terminator));
+                kept_count += 1;
+            }
+        }
+        offset += buffer.len();
+        buffer.clear();
         line_count += 1;
     }
 
-    info!("Processed {} line(s) of input through {} expression(s)",
-          expr_count, line_count);
+    info!("Kept {} out of {} line(s) of input filtered through {} expression(s)",
+          kept_count, line_count, expr_count);
     Ok(())
 }
 
@@ -300,26 +401,28 @@ pub fn map_words_multi_ctx<R, W>(context: &mut Context,
     let asts = try!(parse_exprs(exprs));
     let expr_count = asts.len();
 
-    let reader = BufReader::new(input);
+    let mut reader = BufReader::new(input);
     let mut writer = BufWriter::new(output);
 
+    let first_index = next_record_index(context);
+    let keep_endings = context.keeps_line_endings();
     let mut word_count = 0;
     {
         // Note that `writer` is taken as a parameter rather than just being
         // captured by the closure, because we need to refer to it mutably
         // in the main loop below.
         let mut maybe_process_word = |word: &mut String,
+                                      (line_number, offset): (usize, usize),
                                       writer: &mut Write| -> io::Result<()> {
             if word.is_empty() {
                 return Ok(());
             }
 
-            context.set(CURRENT, to_value(word.clone()));
+            let coercion = context.coercion();
+            set_input(context, coercion.apply(word.clone()), Value::String(word.clone()));
+            set_position(context, first_index + word_count, line_number, offset);
             let result = try!(process(context, &asts));
-
-            let retval = try!(String::try_from(result)
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)));
-            try!(write!(writer, "{}", retval));
+            try!(write_result(writer, result, ""));
 
             word_count += 1;
             word.clear();
@@ -327,19 +430,33 @@ pub fn map_words_multi_ctx<R, W>(context: &mut Context,
         };
 
         let mut word = String::new();
-        for line in reader.lines() {
-            let line = try!(line);
-            for ch in line.chars() {
-                // Whitespace characters denote word's end, but they are to be
-                // preserved verbatim in the final output.
-                if ch.is_whitespace() {
-                    try!(maybe_process_word(&mut word, &mut writer));
-                    try!(write!(writer, "{}", ch));
-                } else {
-                    word.push(ch);
+        let mut word_position = (0, 0);  // line number & byte offset
+        let mut line_number = 0;
+        let mut offset = 0;
+        let mut buffer = String::new();
+        while try!(reader.read_line(&mut buffer)) > 0 {
+            line_number += 1;
+            {
+                let (line, terminator) = split_terminator(&buffer);
+                for (i, ch) in line.char_indices() {
+                    // Whitespace characters denote word's end, but they are to be
+                    // preserved verbatim in the final output.
+                    if ch.is_whitespace() {
+                        try!(maybe_process_word(&mut word, word_position, &mut writer));
+                        try!(write!(writer, "{}", ch));
+                    } else {
+                        if word.is_empty() {
+                            word_position = (line_number, offset + i);
+                        }
+                        word.push(ch);
+                    }
                 }
+                try!(maybe_process_word(&mut word, word_position, &mut writer));
+                let terminator = if keep_endings { terminator } else { "\n" };
+                try!(write!(writer, "{}", terminator));
             }
-            try!(maybe_process_word(&mut word, &mut writer));
+            offset += buffer.len();
+            buffer.clear();
         }
     }
 
@@ -370,7 +487,7 @@ pub fn map_chars_multi_ctx<R, W>(context: &mut Context,
     let mut char_count = 0;
     {
         let mut process_char = |ch: char| -> io::Result<()> {
-            context.set(CURRENT, Value::from(ch));
+            set_input(context, Value::from(ch), Value::from(ch));
 
             // TODO(xion): consider enforcing for the final result to also be 1-char string
             // and writing those characters as a contiguous string
@@ -408,7 +525,7 @@ pub fn map_chars_multi_ctx<R, W>(context: &mut Context,
 /// (as an integer from 0-255 range), whose result is then passed to the second one, etc.
 /// Expression context is shared throughout.
 ///
-/// The final result -- which has to be a 0-255 integer -- is written then
+/// The final result -- which has to be a 0-255 integer, or nil -- is written then
 /// to the given output stream. This continues for each byte of input.
 pub fn map_bytes_multi_ctx<R, W>(context: &mut Context,
                                  exprs: &[&str],
@@ -426,13 +543,14 @@ pub fn map_bytes_multi_ctx<R, W>(context: &mut Context,
     let mut byte_count = 0;
     for byte in reader.bytes() {
         let byte = try!(byte);
-        context.set(CURRENT, Value::from(byte));
+        set_input(context, Value::from(byte), Value::String(byte.to_string()));
 
         let result = try!(process(context, &asts));
         match *result {
-            Value::Integer(i) if 0 <= i && i < u8::MAX as IntegerRepr => {
+            Value::Integer(i) if 0 <= i && i <= u8::MAX as IntegerRepr => {
                 try!(writer.write_all(&[i as u8]))
             },
+            Value::Empty => {},
             _ => return Err(io::Error::new(io::ErrorKind::InvalidData,
                 format!("expected a byte-sized integer, got {}", result))),
         }
@@ -452,6 +570,9 @@ pub fn map_bytes_multi_ctx<R, W>(context: &mut Context,
 /// The corresponding file is read, and its content is fed to the first expression,
 /// whose result is then passed to the second one, etc.
 /// Expression context is shared throughout.
+///
+/// The path of each file is bound to `_p`, replacing the path of the listing itself (if any),
+/// while `_n`, `_l` and `_o` refer to the position of that path within the listing.
 //
 /// The final result is written then to the given output stream.
 /// This continues for each line (file path) of input.
@@ -463,27 +584,37 @@ pub fn map_files_multi_ctx<R, W>(context: &mut Context,
     let asts = try!(parse_exprs(exprs));
     let expr_count = asts.len();
 
-    let reader = BufReader::new(input);
+    let mut reader = BufReader::new(input);
     let mut writer = BufWriter::new(output);
 
+    let first_index = next_record_index(context);
     let mut file_count = 0;
     let mut total_byte_count = 0;
-    for line in reader.lines() {
-        let path = try!(line).trim().to_owned();
+    let mut offset = 0;
+    let mut buffer = String::new();
+    while try!(reader.read_line(&mut buffer)) > 0 {
+        let path = split_terminator(&buffer).0.trim().to_owned();
 
         // we try to use the file size to preallocate the string
         // which we'll read the content of the file to
-        let mut file = try!(File::open(path));
+        let mut file = try!(File::open(&path));
         let mut content = match file.metadata() {
             Ok(metadata) => String::with_capacity(metadata.len() as usize),
             _ => String::new(),
         };
         let byte_count = try!(file.read_to_string(&mut content));
 
-        context.set(CURRENT, Value::String(content));
+        let content = Value::String(content);
+        set_input(context, content.clone(), content);
+        // The position is that of the file path within the input listing,
+        // while the path itself replaces the path of the listing (if any).
+        set_position(context, first_index + file_count, file_count + 1, offset);
+        context.set(FILE_PATH, Value::String(path));
         let result = try!(process(context, &asts));
         try!(write_result_line(&mut writer, result));
 
+        offset += buffer.len();
+        buffer.clear();
         file_count += 1;
         total_byte_count += byte_count;
     }
@@ -506,8 +637,43 @@ fn parse_exprs(exprs: &[&str]) -> io::Result<Vec<Box<Eval>>> {
     Ok(result)
 }
 
-fn to_value(input: String) -> Value {
-    input.parse::<Value>().unwrap_or_else(|_| Value::String(input))
+/// Set the current input within given Context, along with its raw (string) form
+/// that the typed views of input (like `_i` or `_f`) are derived from.
+fn set_input(context: &mut Context, input: Value, raw: Value) {
+    context.set(CURRENT, input);
+    context.set(RAW_INPUT, raw);
+}
+
+/// Index of the next input record to be processed within given Context.
+///
+/// Records are counted across all the inputs processed within the same Context
+/// (e.g. multiple files), so this continues from the index of the last record, if any.
+fn next_record_index(context: &Context) -> usize {
+    match context.get(RECORD_INDEX) {
+        Some(&Value::Integer(i)) if i >= 0 => i as usize + 1,
+        _ => 0,
+    }
+}
+
+/// Set the metadata about position of the current input record within given Context:
+/// its index (among all the records), the line number it is found at,
+/// and its offset (in bytes) from the beginning of input.
+fn set_position(context: &mut Context, index: usize, line_number: usize, offset: usize) {
+    context.set(RECORD_INDEX, Value::Integer(index as IntegerRepr));
+    context.set(LINE_NUMBER, Value::Integer(line_number as IntegerRepr));
+    context.set(BYTE_OFFSET, Value::Integer(offset as IntegerRepr));
+}
+
+/// Split a line read from input into its content and the line terminator
+/// (which is either \n, \r\n, or nothing if it's the last line of input).
+fn split_terminator(line: &str) -> (&str, &str) {
+    if line.ends_with("\r\n") {
+        (&line[..line.len() - 2], "\r\n")
+    } else if line.ends_with('\n') {
+        (&line[..line.len() - 1], "\n")
+    } else {
+        (line, "")
+    }
 }
 
 fn process<'c>(context: &'c mut Context, exprs: &[Box<Eval>]) -> io::Result<&'c Value> {
@@ -539,8 +705,18 @@ fn maybe_apply_result(result: Value, context: &mut Context) -> EvalResult {
     Ok(result)
 }
 
+#[inline]
 fn write_result_line<W: Write>(output: &mut W, result: &Value) -> io::Result<()> {
+    write_result(output, result, "\n")
+}
+
+/// Write the result to given output, followed by the terminator.
+/// Empty (nil) results are skipped entirely.
+fn write_result<W: Write + ?Sized>(output: &mut W, result: &Value, terminator: &str) -> io::Result<()> {
+    if let Value::Empty = *result {
+        return Ok(());
+    }
     let result = try!(String::try_from(result)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)));
-    write!(output, "{}\n", result)
+    write!(output, "{}{}", result, terminator)
 }
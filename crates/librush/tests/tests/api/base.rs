@@ -67,15 +67,23 @@ fn keys() {
     assert_eq!(join(&(0..ARRAY.len()).collect::<Vec<_>>(), "\n"),
                eval(&format!("keys({})", ARRAY.to_literal())));
 
-    // for objects, it's an array of keys (in any order)
+    // for objects, it's an array of keys
     let object_keys = eval(&format!("keys({})", OBJECT.to_literal()));
     for key in OBJECT.keys() {
         assert!(object_keys.contains(key));
     }
+    // ...in the order they were defined in
+    assert_eq!(unlines!("b", "c", "a"), eval("keys({b: 1, c: 2, a: 3})"));
 
     assert_eval_error("keys(|x| x)")
 }
-// TODO: tests for values()
+
+#[test]
+fn values() {
+    assert_eq!("", eval("values({})"));
+    assert_eq!(unlines!("1", "2", "3"), eval("values({b: 1, c: 2, a: 3})"));
+    assert_eval_error("values([1, 2])");
+}
 
 mod index {
     use util::*;
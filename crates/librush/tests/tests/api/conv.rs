@@ -23,6 +23,30 @@ fn int() {
     assert_eval_error(&format!("int({})", "{}"));
 }
 
+#[test]
+fn frombase() {
+    assert_eq!("255", eval("frombase(16, \"ff\")"));
+    assert_eq!("255", eval("frombase(16, \"0xFF\")"));
+    assert_eq!("-5", eval("frombase(2, \"-101\")"));
+    assert_eq!("35", eval("frombase(36, \"z\")"));
+    assert_eq!(unlines!("10", "255"), eval("map(frombase(16), [\"a\", \"ff\"])"));
+    assert_eval_error("frombase(2, \"12\")");
+    assert_eval_error("frombase(1, \"12\")");
+    assert_eval_error("frombase(10, 12)");
+}
+
+#[test]
+fn parseint() {
+    assert_eq!("31", eval("parseint(\"0x1f\")"));
+    assert_eq!("15", eval("parseint(\"0o17\")"));
+    assert_eq!("-5", eval("parseint(\"-0b101\")"));
+    assert_eq!("1000", eval("parseint(\"1_000\")"));
+    assert_eq!("42", eval("parseint(42)"));
+    assert_eval_error("parseint(\"0x\")");
+    assert_eval_error("parseint(\"1__000\")");
+    assert_eval_error("parseint(\"foo\")");
+}
+
 #[test]
 fn float() {
     assert_apply_error("float(_)", "foobar");
@@ -43,6 +67,7 @@ fn bool() {
     assert_eq!("false", eval(&format!("bool({})", "{}")));
     assert_eq!("true", eval(&format!("bool({})", "[3]")));
     assert_eq!("true", eval(&format!("bool({})", "{foo: 4}")));
+    assert_eq!("false", eval("bool(nil)"));
 }
 
 #[test]
@@ -53,13 +78,55 @@ fn array() {
     assert_apply_error("array(_)", "false");
     assert_eq!(unlines!("3", "4"), eval(&format!("array({})", "[3,4]")));
 
-    // the order of object keys is unspecified
-    let keys: Vec<_> = eval(&format!("array({})", "{foo: 3, bar: 4}")).split("\n")
-        .map(String::from).collect();
-    for &s in &["foo", "bar"] {
-        assert!(keys.contains(&String::from(s)));
-    }
+    assert_eq!(unlines!("foo", "bar"), eval(&format!("array({})", "{foo: 3, bar: 4}")));
 }
 
 // TODO(xion): tests for csv() function
-// TODO(xion): tests for json() function
+
+mod json {
+    use util::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("42", eval(r#"json("42")"#));
+        assert_eq!(unlines!("1", "2"), eval(r#"json("[1, 2]")"#));
+        assert_eq!("2", eval(r#"json("{\"a\": {\"b\": 2}}")[a][b]"#));
+        assert_eq!(unlines!("b", "a", "c"), eval(r#"keys(json("{\"b\": 1, \"a\": 2, \"c\": 3}"))"#));
+        assert_eq!(unlines!("a", "b"), eval(r#"keys(json("{\"a\": 1, \"b\": 2, \"a\": 3}"))"#));
+        assert_eq!("3", eval(r#"json("{\"a\": 1, \"b\": 2, \"a\": 3}")[a]"#));
+        assert_eval_error(r#"json("{")"#);
+        assert_eval_error(r#"json("[1] 2")"#);
+        assert_eval_error(r#"json("{a: 1}")"#);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!("[]", eval("json([])"));
+        assert_eq!("{}", eval("json({})"));
+        assert_eq!(r#"[1,"a",null]"#, eval("json([1, a, nil])"));
+        assert_eq!(r#"{"b":1,"a":[2,3]}"#, eval("json({b: 1, a: [2, 3]})"));
+        assert_eval_error("json(42)");
+    }
+
+    #[test]
+    fn roundtrip() {
+        const JSON: &'static str = r#"{"z":1,"y":{"x":true,"w":null},"v":[1.5,"u"]}"#;
+        assert_eq!(JSON, apply("json(json(_))", JSON));
+    }
+
+    #[test]
+    fn pretty() {
+        assert_eq!("{}", eval("json({}, 2)"));
+        assert_eq!(unlines!("{", "  \"a\": 1", "}"), eval("json({a: 1}, 2)"));
+        assert_eq!(unlines!("{",
+                            "    \"b\": [",
+                            "        1,",
+                            "        2",
+                            "    ],",
+                            "    \"a\": {}",
+                            "}"),
+                   eval("json({b: [1, 2], a: {}}, 4)"));
+        assert_eval_error("json({a: 1}, -1)");
+        assert_eval_error("json({a: 1}, foo)");
+    }
+}
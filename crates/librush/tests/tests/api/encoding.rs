@@ -0,0 +1,62 @@
+//! Tests for the encoding functions.
+
+use util::*;
+
+
+#[test]
+fn base64() {
+    assert_eq!("Zm9vYmFy", eval("b64encode(\"foobar\")"));
+    assert_eq!("Zm9vYg==", eval("b64encode(\"foob\")"));
+    assert_eq!("-_8", eval("b64encode([251, 255], \"url\")"));
+    assert_eq!("+/8=", eval("b64encode([251, 255])"));
+    assert_eq!("foobar", eval("b64decode(\"Zm9vYmFy\")"));
+    assert_eq!("foob", eval("b64decode(\"Zm9vYg\")"));
+    assert_eq!(unlines!("251", "255"), eval("b64decode(\"-_8\")"));
+    assert_eval_error("b64encode(foo, bar)");
+    assert_eval_error("b64encode([256])");
+    assert_eval_error("b64decode(\"!!!\")");
+}
+
+#[test]
+fn hex() {
+    assert_eq!("666f6f", eval("hexencode(foo)"));
+    assert_eq!("00ff", eval("hexencode([0, 255])"));
+    assert_eq!("foo", eval("hexdecode(\"666F6F\")"));
+    assert_eval_true("hexdecode(hexencode(utf8(\"żółw\"))) == \"żółw\"");
+    assert_eval_error("hexdecode(\"abc\")");
+    assert_eval_error("hexencode(42)");
+}
+
+#[test]
+fn url() {
+    assert_eq!("a%20b%26c%3Dd~", eval("urlencode(\"a b&c=d~\")"));
+    assert_eq!("%C5%BC", eval("urlencode(\"ż\")"));
+    assert_eq!("a b&c=d", eval("urldecode(\"a+b%26c%3dd\")"));
+    assert_eq!("ż", eval("urldecode(\"%C5%BC\")"));
+    assert_eval_error("urldecode(\"100%\")");
+    assert_eval_error("urldecode(\"%zz\")");
+}
+
+#[test]
+fn html() {
+    assert_eq!("&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;",
+               eval("htmlescape(\"<a href=\\\"x\\\">Tom & Jerry's</a>\")"));
+    assert_eq!("<b>'&'</b>", eval("htmlunescape(\"&lt;b&gt;&apos;&amp;&#x27;&lt;/b&gt;\")"));
+    assert_eq!("A&B &foo; &", eval("htmlunescape(\"&#65;&B &foo; &\")"));
+}
+
+#[test]
+fn shquote() {
+    assert_eq!("foo/bar.txt", eval("shquote(\"foo/bar.txt\")"));
+    assert_eq!("''", eval("shquote(\"\")"));
+    assert_eq!("'a b'", eval("shquote(\"a b\")"));
+    assert_eq!("'it'\"'\"'s'", eval("shquote(\"it's\")"));
+    assert_eval_error("shquote(42)");
+}
+
+#[test]
+fn jsonquote() {
+    assert_eq!("\"foo\"", eval("jsonquote(foo)"));
+    assert_eq!("\"a\\\"b\\nc\"", eval("jsonquote(\"a\\\"b\\nc\")"));
+    assert_eval_error("jsonquote([])");
+}
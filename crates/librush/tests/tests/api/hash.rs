@@ -0,0 +1,51 @@
+//! Tests for the hash functions.
+
+use util::*;
+
+
+#[test]
+fn md5() {
+    assert_eq!("d41d8cd98f00b204e9800998ecf8427e", eval("md5(\"\")"));
+    assert_eq!("900150983cd24fb0d6963f7d28e17f72", eval("md5(\"abc\")"));
+    assert_eq!("900150983cd24fb0d6963f7d28e17f72", eval("md5([97, 98, 99])"));
+    assert_eval_true("len(md5(\"abc\", \"bytes\")) == 16");
+    assert_eval_error("md5(42)");
+    assert_eval_error("md5(\"abc\", \"base64\")");
+}
+
+#[test]
+fn sha() {
+    assert_eq!("a9993e364706816aba3e25717850c26c9cd0d89d", eval("sha1(\"abc\")"));
+    assert_eq!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+               eval("sha256(\"abc\")"));
+    assert_eval_true("len(sha512(\"abc\")) == 128");
+    assert_eval_true("sha512(\"abc\", \"bytes\")[0] == 221");  // 0xdd
+}
+
+#[test]
+fn hmac() {
+    const DATA: &'static str = "\"The quick brown fox jumps over the lazy dog\"";
+    assert_eq!("80070713463e7749b90c2dc24911e275",
+               eval(&format!("hmac(\"key\", {}, \"md5\")", DATA)));
+    assert_eq!("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+               eval(&format!("hmac(\"key\", {}, \"sha256\")", DATA)));
+    assert_eval_error(&format!("hmac(\"key\", {}, \"sha3\")", DATA));
+}
+
+#[test]
+fn non_cryptographic() {
+    assert_eq!("cbf43926", eval("crc32(\"123456789\")"));
+    assert_eq!(unlines!("203", "244", "57", "38"), eval("crc32(\"123456789\", \"bytes\")"));
+    assert_eq!("cbf29ce484222325", eval("fnv(\"\")"));
+    assert_eq!("af63dc4c8601ec8c", eval("fnv(\"a\")"));
+    assert_eq!("ef46db3751d8e999", eval("xxhash(\"\")"));
+}
+
+#[test]
+fn hash() {
+    assert_eval_true("hash(foo) == hash(\"foo\")");
+    assert_eval_true("hash(foo) != hash(bar)");
+    assert_eval_true("hash(42) == hash(\"42\")");
+    assert_eval_true("hash(\"\") >= 0");
+    assert_eval_error("hash([])");
+}
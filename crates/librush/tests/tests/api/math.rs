@@ -0,0 +1,86 @@
+//! Tests for the math functions.
+
+use util::*;
+
+
+#[test]
+fn constants() {
+    assert_eval_true("exp(1) > 2.718 && exp(1) < 2.719");
+    assert_eval_true("tau == 2 * pi");
+    assert_eval_true("inf > 1000000000");
+}
+
+#[test]
+fn logarithms() {
+    assert_eq!("3.0", eval("log2(8)"));
+    assert_eq!("2.0", eval("log10(100)"));
+    assert_eval_true("abs(log(3, 81) - 4) < 0.000001");
+    assert_eval_error("log(1, 42)");
+    assert_eval_error("log(0, 42)");
+    assert_eval_error("log2(foo)");
+}
+
+#[test]
+fn trigonometry() {
+    assert_eq!("0.0", eval("sin(0)"));
+    assert_eq!("1.0", eval("cos(0)"));
+    assert_eval_true("abs(tan(pi / 4) - 1) < 0.000001");
+    assert_eval_true("abs(atan2(1, 1) - pi / 4) < 0.000001");
+    assert_eval_true("abs(degrees(pi) - 180) < 0.000001");
+    assert_eq!("5.0", eval("hypot(3, 4)"));
+    assert_eval_error("sin(\"foo\")");
+}
+
+#[test]
+fn round() {
+    assert_eq!("3.0", eval("round(3.14159)"));
+    assert_eq!("42", eval("round(42)"));
+    assert_eval_error("round(foo)");
+}
+
+#[test]
+fn roundn() {
+    assert_eq!("3.14", eval("roundn(2, 3.14159)"));
+    assert_eq!("42", eval("roundn(2, 42)"));
+    assert_eq!("1200", eval("roundn(-2, 1234)"));
+    assert_eq!("0", eval("roundn(-100, 1234)"));
+    assert_eq!("1.5", eval("roundn(9223372036854775807, 1.5)"));
+    assert_eq!("0.0", eval("roundn(-9223372036854775807, 1.5)"));
+    assert_eq!(unlines!("3.1", "2.7"), eval("map(roundn(1), [3.14159, 2.71828])"));
+    assert_eval_error("roundn(-19, 9223372036854775807)");
+    assert_eval_error("roundn(1.5, 3.14)");
+    assert_eval_error("roundn(2, foo)");
+}
+
+#[test]
+fn clamp() {
+    assert_eq!("5", eval("clamp(1, 5, 10)"));
+    assert_eq!("1", eval("clamp(1, 5, -3)"));
+    assert_eq!("3", eval("clamp(1, 5, 3)"));
+    assert_eq!("0.5", eval("clamp(0, 1, 0.5)"));
+    assert_eq!("1.0", eval("clamp(0, 1, 1.5)"));
+    assert_eval_error("clamp(5, 1, 3)");
+}
+
+#[test]
+fn number_theory() {
+    assert_eq!("6", eval("gcd(12, 18)"));
+    assert_eq!("36", eval("lcm(12, 18)"));
+    assert_eq!("0", eval("lcm(0, 5)"));
+    assert_eq!(unlines!("3", "1"), eval("divmod(7, 2)"));
+    assert_eval_error("divmod(7, 0)");
+    assert_eval_error("divmod(-9223372036854775807 - 1, -1)");
+    assert_eval_error("gcd(-9223372036854775807 - 1, -1)");
+    assert_eval_error("gcd(-9223372036854775807 - 1, 0)");
+    assert_eval_error("lcm(9223372036854775807, 2)");
+    assert_eval_true("isprime(97)");
+    assert_eval_false("isprime(91)");
+    assert_eval_false("isprime(1)");
+    assert_eq!("120", eval("factorial(5)"));
+    assert_eq!("1", eval("factorial(0)"));
+    assert_eval_error("factorial(-1)");
+    assert_eq!("2432902008176640000", eval("factorial(20)"));
+    assert_eval_error("factorial(21)");
+    assert_eval_error("factorial(100)");
+    assert_eval_error("factorial(9223372036854775807)");
+}
@@ -2,7 +2,14 @@
 
 mod base;
 mod conv;
+mod encoding;
+mod hash;
+mod math;
+mod objects;
+mod stats;
 mod strings;
+mod time;
+mod url;
 
 
 // TODO: tests for the random module
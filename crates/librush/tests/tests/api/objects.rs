@@ -0,0 +1,136 @@
+//! Tests for the object API functions.
+
+use util::*;
+
+
+#[test]
+fn has() {
+    assert_eval_true("has(a, {a: 1})");
+    assert_eval_false("has(b, {a: 1})");
+    assert_eval_false("has(a, {})");
+    assert_eval_error("has(0, [1])");
+    assert_eval_error("has(a, a)");
+}
+
+mod merge {
+    use util::*;
+
+    #[test]
+    fn shallow() {
+        assert_eq!("{}", eval("merge({}, {})"));
+        assert_eq!(unlines!("a", "b", "c"), eval("keys(merge({a: 1, b: 2}, {c: 3}))"));
+        assert_eq!("3", eval("merge({a: 1, b: 2}, {b: 3})[b]"));
+        assert_eq!(unlines!("a", "b"), eval("keys(merge({a: 1, b: 2}, {a: 3}))"));
+        assert_eq!("{\"b\":2}", eval("merge({a: {b: 1}}, {a: {b: 2}})[a]"));
+        assert_eval_error("merge({}, [])");
+        assert_eval_error("merge([], {})");
+    }
+
+    #[test]
+    fn plus_operator() {
+        assert_eq!(unlines!("a", "b"), eval("keys({a: 1} + {b: 2})"));
+        assert_eq!("2", eval("({a: 1} + {a: 2})[a]"));
+    }
+
+    #[test]
+    fn deep() {
+        assert_eq!(unlines!("b", "c"), eval("keys(deepmerge({a: {b: 1}}, {a: {c: 2}})[a])"));
+        assert_eq!("2", eval("deepmerge({a: {b: 1}}, {a: {b: 2}})[a][b]"));
+        assert_eq!("2", eval("deepmerge({a: {b: 1}}, {a: 2})[a]"));
+        assert_eval_error("deepmerge({}, 42)");
+    }
+}
+
+#[test]
+fn entries() {
+    assert_eq!("", eval("entries({})"));
+    assert_eq!("2", eval("len(entries({a: 1, b: 2}))"));
+    assert_eq!("b", eval("entries({a: 1, b: 2})[1][0]"));
+    assert_eq!("2", eval("entries({a: 1, b: 2})[1][1]"));
+    assert_eval_error("entries([])");
+
+    assert_eq!("{}", eval("fromentries([])"));
+    assert_eq!("2", eval("fromentries([[a, 1], [b, 2]])[b]"));
+    assert_eq!(unlines!("a", "b"), eval("keys(fromentries(entries({a: 1, b: 2})))"));
+    assert_eval_error("fromentries([[a, 1, 2]])");
+    assert_eval_error("fromentries([a])");
+    assert_eval_error("fromentries({})");
+}
+
+#[test]
+fn mapvalues() {
+    assert_eq!("{}", eval("mapvalues(|x| x + 1, {})"));
+    assert_eq!("2", eval("mapvalues(|x| x + 1, {a: 1})[a]"));
+    assert_eval_error("mapvalues(|x| x + 1, [1])");
+    assert_eval_error("mapvalues(a, {a: 1})");
+}
+
+#[test]
+fn mapkeys() {
+    assert_eq!("{}", eval("mapkeys(|k| k + k, {})"));
+    assert_eq!("1", eval("mapkeys(|k| k + k, {a: 1})[aa]"));
+    assert_eq!(unlines!("xa", "xb"), eval("keys(mapkeys(|k| x + k, {a: 1, b: 2}))"));
+    assert_eval_error("mapkeys(|k| k, [1])");
+}
+
+#[test]
+fn filterkeys() {
+    assert_eq!("{}", eval("filterkeys(|k| k != a, {a: 1})"));
+    assert_eq!(unlines!("b", "c"), eval("keys(filterkeys(|k| k != a, {a: 1, b: 2, c: 3}))"));
+    assert_eval_error("filterkeys(|k| true, [1])");
+}
+
+#[test]
+fn sortkeys() {
+    assert_eq!("{}", eval("sortkeys({})"));
+    assert_eq!(r#"{"a":2,"b":1}"#, eval("sortkeys({b: 1, a: 2})"));
+    assert_eq!(r#"{"a":{"c":1,"d":2},"b":[{"e":3,"f":4}]}"#,
+               eval("sortkeys({b: [{f: 4, e: 3}], a: {d: 2, c: 1}})"));
+    assert_eq!(r#"{"x":1,"y":2}"#, eval("sortkeys([{y: 2, x: 1}])"));
+    assert_eval_error("sortkeys(foo)");
+}
+
+mod get {
+    use util::*;
+
+    #[test]
+    fn dotted_path() {
+        assert_eq!("1", eval("get(a, {a: 1}, 0)"));
+        assert_eq!("2", eval("get(\"a.b\", {a: {b: 2}}, 0)"));
+        assert_eq!("3", eval("get(\"a.1\", {a: [2, 3]}, 0)"));
+        assert_eq!("3", eval("get(\"a.-1\", {a: [2, 3]}, 0)"));
+        assert_eq!("0", eval("get(\"a.c\", {a: {b: 2}}, 0)"));
+        assert_eq!("0", eval("get(\"a.b.c\", {a: {b: 2}}, 0)"));
+        assert_eq!("0", eval("get(\"a.5\", {a: [2, 3]}, 0)"));
+    }
+
+    #[test]
+    fn array_path() {
+        assert_eq!("2", eval("get([a, b], {a: {b: 2}}, 0)"));
+        assert_eq!("3", eval("get([a, 1], {a: [2, 3]}, 0)"));
+        assert_eq!("2", eval("get([0, a], [{a: 2}], 0)"));
+        assert_eq!("0", eval("get([b], {a: 1}, 0)"));
+        assert_eval_error("get(42, {a: 1}, 0)");
+    }
+}
+
+mod setin {
+    use util::*;
+
+    #[test]
+    fn objects() {
+        assert_eq!("2", eval("setin(a, 2, {a: 1})[a]"));
+        assert_eq!("2", eval("setin(\"a.b\", 2, {})[a][b]"));
+        assert_eq!(unlines!("a", "b", "c"), eval("keys(setin(b, 5, {a: 1, b: 2, c: 3}))"));
+        assert_eq!("1", eval("setin(\"a.c\", 2, {a: {b: 1}})[a][b]"));
+        assert_eval_error("setin(\"a.b\", 2, {a: 1})");
+    }
+
+    #[test]
+    fn arrays() {
+        assert_eq!(unlines!("1", "5"), eval("setin([1], 5, [1, 2])"));
+        assert_eq!("5", eval("setin(\"a.0\", 5, {a: [1]})[a][0]"));
+        assert_eval_error("setin([2], 5, [1, 2])");
+        assert_eval_error("setin([a], 5, [1, 2])");
+    }
+}
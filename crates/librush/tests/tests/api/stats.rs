@@ -0,0 +1,87 @@
+//! Tests for the statistical functions.
+
+use util::*;
+
+
+#[test]
+fn mean() {
+    assert_eq!("2.0", eval("mean([1, 2, 3])"));
+    assert_eq!("2.5", eval("mean([1, 2.0, 3, 4])"));
+    assert_eval_true("mean([]) == nil");
+    assert_eval_error("mean([1, foo])");
+    assert_eval_error("mean(42)");
+}
+
+#[test]
+fn median() {
+    assert_eq!("2", eval("median([3, 1, 2])"));
+    assert_eq!("2.5", eval("median([4, 1, 3, 2])"));
+    assert_eval_true("median([]) == nil");
+    assert_eval_error("median(\"foo\")");
+}
+
+#[test]
+fn mode() {
+    assert_eq!("2", eval("mode([1, 2, 3, 2])"));
+    assert_eq!("a", eval("mode([a, b, b, a])"));
+    assert_eval_true("mode([]) == nil");
+    assert_eval_error("mode(42)");
+}
+
+#[test]
+fn percentile() {
+    assert_eq!("2.5", eval("percentile(50, [1, 2, 3, 4])"));
+    assert_eq!("1.0", eval("percentile(0, [4, 3, 2, 1])"));
+    assert_eq!("4.0", eval("percentile(100, [4, 3, 2, 1])"));
+    assert_eq!(unlines!("2.0", "4.0"), eval("percentile([25, 75], [1, 2, 3, 4, 5])"));
+    assert_eval_error("percentile(101, [1, 2])");
+    assert_eval_error("percentile(-1, [1, 2])");
+    assert_eval_error("percentile(foo, [1, 2])");
+}
+
+#[test]
+fn quantiles() {
+    assert_eq!(unlines!("2.0", "3.0", "4.0"), eval("quantiles(4, [1, 2, 3, 4, 5])"));
+    assert_eq!("3.0", eval("quantiles(2, [1, 2, 3, 4, 5])"));
+    assert_eval_error("quantiles(0, [1, 2])");
+}
+
+#[test]
+fn variance_and_stddev() {
+    assert_eq!("4.0", eval("variance([2, 4, 4, 4, 5, 5, 7, 9])"));
+    assert_eq!("2.0", eval("stddev([2, 4, 4, 4, 5, 5, 7, 9])"));
+    assert_eq!("0.0", eval("stddev([42])"));
+    assert_eval_true("stddev([]) == nil");
+    assert_eval_error("variance([true])");
+}
+
+#[test]
+fn zscore() {
+    assert_eq!(unlines!("-1.0", "1.0"), eval("zscore([1, 3])"));
+    assert_eq!("", eval("zscore([])"));
+    assert_eval_error("zscore([5, 5])");
+    assert_eval_error("zscore([5])");
+    assert_eval_error("zscore(foo)");
+}
+
+#[test]
+fn correlation() {
+    assert_eq!("1.0", eval("correlation([1, 2, 3], [2, 4, 6])"));
+    assert_eq!("-1.0", eval("correlation([1, 2, 3], [3, 2, 1])"));
+    assert_eval_error("correlation([1, 2], [1, 2, 3])");
+    assert_eval_error("correlation([1, 2, 3], [5, 5, 5])");
+    assert_eval_error("correlation([7, 7], [1, 2])");
+}
+
+#[test]
+fn histogram() {
+    assert_eval_true("histogram(2, [1, 2, 3, 4])[0][\"count\"] == 2");
+    assert_eval_true("histogram(2, [1, 2, 3, 4])[1][\"count\"] == 2");
+    assert_eval_true("histogram(2, [1, 2, 3, 4])[1][\"max\"] == 4.0");
+    assert_eval_true("histogram([0, 10, 20], [5, 15, 25])[0][\"count\"] == 1");
+    assert_eval_true("histogram([0, 10, 20], [5, 15, 20])[1][\"count\"] == 2");
+    assert_eval_error("histogram([10, 0], [1, 2])");
+    assert_eval_error("histogram(0, [1, 2])");
+    assert_eval_error("histogram(1000000000000, [1, 2])");
+    assert_eval_error("histogram(9223372036854775807, [1, 2])");
+}
@@ -48,6 +48,78 @@ mod split {
         assert_eval_error(&format!("split(X, {})", "[]"));
         assert_eval_error(&format!("split(X, {})", "{}"));
     }
+
+    #[test]
+    fn splitn() {
+        assert_eq!(unlines!("key", "value=with=equals"),
+                   eval("splitn(1, \"=\", \"key=value=with=equals\")"));
+        assert_eq!("a=b", eval("splitn(0, \"=\", \"a=b\")"));
+        assert_eq!(unlines!("a", "b", "c"), eval("splitn(5, \"=\", \"a=b=c\")"));
+        assert_eq!(unlines!("a", "b c"), eval("splitn(1, /\\s+/, \"a  b c\")"));
+        assert_eval_error("splitn(-1, \"=\", \"a=b\")");
+        assert_eval_error("splitn(x, \"=\", \"a=b\")");
+    }
+
+    #[test]
+    fn rsplitn() {
+        assert_eq!(unlines!("a=b", "c"), eval("rsplitn(1, \"=\", \"a=b=c\")"));
+        assert_eq!(unlines!("a", "b", "c"), eval("rsplitn(2, \"=\", \"a=b=c\")"));
+        assert_eq!("abc", eval("rsplitn(1, \"=\", abc)"));
+        assert_eq!(unlines!("a  b", "c"), eval("rsplitn(1, /\\s+/, \"a  b c\")"));
+        assert_eq!(unlines!("a", "b", "c"), eval("rsplitn(5, /\\s+/, \"a  b c\")"));
+        assert_eq!("a b", eval("rsplitn(0, /\\s+/, \"a b\")"));
+        assert_eval_error("rsplitn(1, 42, \"a=b\")");
+    }
+
+    #[test]
+    fn curried() {
+        assert_eq!(unlines!("KEY", "VALUE=X"),
+                   apply("splitn(1, \"=\") & map(upper)", "key=value=x"));
+        assert_eq!(unlines!("KEY=VALUE", "X"),
+                   apply("rsplitn(1, \"=\") & map(upper)", "key=value=x"));
+        assert_eq!(unlines!("a", "b"), apply("split(\",\") & map(trim)", "a, b"));
+    }
+}
+
+mod partition {
+    use util::*;
+
+    #[test]
+    fn first() {
+        assert_eq!(unlines!("key", "=", "a=b"), eval("partition(\"=\", \"key=a=b\")"));
+        assert_eq!(unlines!("abc", "", ""), eval("partition(\"=\", abc)"));
+        assert_eq!(unlines!("GET", "  ", "/ HTTP"), eval("partition(/\\s+/, \"GET  / HTTP\")"));
+        assert_eval_error("partition(\"=\", 42)");
+    }
+
+    #[test]
+    fn last() {
+        assert_eq!(unlines!("key=a", "=", "b"), eval("rpartition(\"=\", \"key=a=b\")"));
+        assert_eq!(unlines!("", "", "abc"), eval("rpartition(\"=\", abc)"));
+        assert_eq!(unlines!("a 1 b", "2", " c"), eval("rpartition(/[0-9]/, \"a 1 b2 c\")"));
+    }
+}
+
+#[test]
+fn shlex() {
+    assert_eq!(unlines!("ls", "-l", "my file"), eval(r#"shlex("ls -l 'my file'")"#));
+    assert_eq!(unlines!("echo", "say \"hi\"", "$HOME"),
+               eval(r#"shlex("echo \"say \\\"hi\\\"\" \\$HOME")"#));
+    assert_eq!(unlines!("a", "", "b"), eval(r#"shlex("a '' b")"#));
+    assert_eq!(unlines!("ab c"), eval(r#"shlex("a'b c'")"#));
+    assert_eq!("", eval(r#"shlex("   ")"#));
+    assert_eval_error(r#"shlex("'unterminated")"#);
+    assert_eval_error("shlex(42)");
+}
+
+#[test]
+fn splitlines() {
+    assert_eq!(unlines!("a", "b", "c"), eval("splitlines(\"a\\nb\\r\\nc\\n\")"));
+    assert_eq!(unlines!("a\n", "b\r\n", "c"), eval("splitlines(\"a\\nb\\r\\nc\", true)"));
+    assert_eq!(unlines!("a", "", "b"), eval("splitlines(\"a\\r\\rb\")"));
+    assert_eq!("", eval("splitlines(\"\")"));
+    assert_eval_error("splitlines(\"a\", 1)");
+    assert_eval_error("splitlines(42)");
 }
 
 #[test]
@@ -63,9 +135,59 @@ fn join_() {
     assert_eval_error(&format!("join(X, {})", "{}"));
 }
 
-// TODO(xion): tests for sub() and sub1(), especially w/ regex and replacement function
+// TODO(xion): tests for sub() and sub1(), especially w/ replacement function
 // TODO(xion): tests for rsub1()
 
+mod sub {
+    use util::*;
+
+    #[test]
+    fn backrefs() {
+        assert_eq!("b=a", apply(r#"sub(/(\w+)=(\w+)/, "\\2=\\1", _)"#, "a=b"));
+        assert_eq!("b=a", apply(r#"sub(/(\w+)=(\w+)/, "$2=$1", _)"#, "a=b"));
+        assert_eq!("bx=a", apply(r#"sub(/(\w+)=(\w+)/, "${2}x=${1}", _)"#, "a=b"));
+        assert_eq!("b=a", apply(r#"sub(/(?P<k>\w+)=(?P<v>\w+)/, "${v}=$k", _)"#, "a=b"));
+        assert_eq!("$a", apply(r#"sub(/(\w+)/, "$$$1", _)"#, "a"));
+        assert_eq!(r"\1", apply(r#"sub(/(\w+)/, "\\\\1", _)"#, "a"));
+        assert_eq!(r"a\b", apply(r#"sub(/-/, "\\\\", _)"#, "a-b"));
+        assert_eq!("[]", apply(r#"sub(/(\w+)/, "[\\9]", _)"#, "a"));
+        assert_eq!("x-x y-y", apply(r#"sub(/(\w)/, "\\1-\\1", _)"#, "x y"));
+        assert_eq!("x-x y", apply(r#"sub1(/(\w)/, "\\1-\\1", _)"#, "x y"));
+    }
+}
+
+mod match_ {
+    use util::*;
+
+    #[test]
+    fn match_() {
+        assert_eq!("a=1", apply(r#"match(/(\w+)=(\d+)/, _)["0"]"#, "a=1"));
+        assert_eq!("1", apply(r#"match(/(\w+)=(\d+)/, _)["2"]"#, "a=1"));
+        assert_eq!("a", apply(r#"match(/(?P<key>\w+)=(?P<val>\d+)/, _)["key"]"#, "a=1"));
+        assert_eq!("a", apply(r#"match(/(?P<key>\w+)=(?P<val>\d+)/, _)["1"]"#, "a=1"));
+        assert_eq!("true", apply(r#"match(/x(y)?/, _)["1"] == nil"#, "x"));
+        assert_eq!("true", apply(r#"match(/\d+/, _) == nil"#, "foo"));
+        assert_apply_error(r#"match(/\d+/, _)"#, "42");
+        assert_eval_error(r#"match("\\d+", foo)"#);
+    }
+
+    #[test]
+    fn matchall() {
+        assert_eq!("0", apply(r#"len(matchall(/\d/, _))"#, "foo"));
+        assert_eq!("3", apply(r#"len(matchall(/\d/, _))"#, "a1b2c3"));
+        assert_eq!("2", apply(r#"matchall(/(\w)(\d)/, _)[1]["2"]"#, "a1b2c3"));
+        assert_apply_error(r#"matchall(/\d/, _)"#, "42");
+    }
+
+    #[test]
+    fn captures() {
+        assert_eq!(unlines!("a", "1"), apply(r#"captures(/(\w+)=(\d+)/, _)"#, "a=1"));
+        assert_eq!("0", apply(r#"len(captures(/\w+/, _))"#, "foo"));
+        assert_eq!("true", apply(r#"captures(/(\d+)/, _) == nil"#, "foo"));
+        assert_apply_error(r#"captures(/\d/, _)"#, "42");
+    }
+}
+
 mod before {
     use util::*;
 
@@ -125,3 +247,204 @@ mod after {
         assert_eval_error("after(/foo/, {})");
     }
 }
+
+mod case {
+    use util::*;
+
+    #[test]
+    fn upper_lower() {
+        assert_eq!("FOO BAR", eval("upper(\"foo Bar\")"));
+        assert_eq!("foo bar", eval("lower(\"FOO Bar\")"));
+        assert_eq!("STRASSE", eval("upper(\"straße\")"));
+        assert_eq!("àéî", eval("lower(\"ÀÉÎ\")"));
+        assert_eq!(unlines!("A", "B"), eval("upper([a, b])"));
+        assert_eval_error("upper(42)");
+        assert_eval_error("lower({})");
+    }
+
+    #[test]
+    fn title_capitalize() {
+        assert_eq!("Hello World", eval("title(\"hello wORLD\")"));
+        assert_eq!("Don't Stop-Me", eval("title(\"don't stop-me\")"));
+        assert_eq!("Élan Vital", eval("title(\"élan vital\")"));
+        assert_eq!("Hello world", eval("capitalize(\"hELLO WORLD\")"));
+        assert_eq!("", eval("capitalize(\"\")"));
+        assert_eval_error("title(42)");
+    }
+
+    #[test]
+    fn casefold() {
+        assert_eval_true("casefold(\"STRASSE\") == casefold(\"straße\")");
+        assert_eval_true("casefold(\"ΣΟΦΟΣ\") == casefold(\"σοφος\")");
+        assert_eq!(unlines!("abc", "ss"), eval("casefold([ABC, \"ß\"])"));
+        assert_eval_error("casefold(true)");
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!("parse_http_request", eval("snake(parseHTTPRequest)"));
+        assert_eq!("parse-http-request", eval("kebab(\"Parse HTTP request\")"));
+        assert_eq!("PARSE_HTTP_REQUEST", eval("constant(\"parse-http-request\")"));
+        assert_eq!("parseHttpRequest", eval("camel(\"parse_http_request\")"));
+        assert_eq!("ParseHttpRequest", eval("pascal(\"parse http request\")"));
+        assert_eq!("version2_beta", eval("snake(version2Beta)"));
+        assert_eq!("", eval("camel(\"__\")"));
+        assert_eq!(unlines!("foo_bar", "baz_qux"), eval("snake([fooBar, BazQux])"));
+        assert_eval_error("snake(42)");
+    }
+}
+
+mod unicode {
+    use rush::{self, Context, Value};
+    use util::*;
+
+    #[test]
+    fn normalization() {
+        assert_eq!("\u{e9}", eval("nfc(\"e\u{301}\")"));
+        assert_eq!("e\u{301}", eval("nfd(\"\u{e9}\")"));
+        assert_eq!("fi", eval("nfkc(\"\u{fb01}\")"));
+        assert_eq!("fi", eval("nfkd(\"\u{fb01}\")"));
+        assert_eq!("\u{fb01}", eval("nfc(\"\u{fb01}\")"));
+        assert_eval_error("nfc(42)");
+    }
+
+    #[test]
+    fn graphemes() {
+        assert_eq!(unlines!("e\u{301}", "x"), eval("graphemes(\"e\u{301}x\")"));
+        assert_eq!("", eval("graphemes(\"\")"));
+        assert_eq!("2", eval("glen(\"e\u{301}x\")"));
+        assert_eq!("1", eval("glen(\"\u{1f1f5}\u{1f1f1}\")"));
+        assert_eq!("3", eval("len(\"e\u{301}x\")"));
+        assert_eval_error("glen([])");
+    }
+
+    #[test]
+    fn width() {
+        assert_eq!("3", eval("width(abc)"));
+        assert_eq!("4", eval("width(\"\u{6f22}\u{5b57}\")"));
+        assert_eq!("1", eval("width(\"e\u{301}\")"));
+        assert_eval_error("width(42)");
+    }
+
+    #[test]
+    fn category() {
+        assert_eq!("Lu", eval("category(A)"));
+        assert_eq!("Ll", eval("category(\"\u{e9}\")"));
+        assert_eq!("Lo", eval("category(\"\u{6f22}\")"));
+        assert_eq!("Nd", eval("category(\"7\")"));
+        assert_eq!("Zs", eval("category(\" \")"));
+        assert_eq!("Po", eval("category(\"!\")"));
+        assert_eq!("Sc", eval("category(\"$\")"));
+        assert_eval_error("category(ab)");
+        assert_eval_error("category(\"\")");
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eval_true("isalpha(\"h\u{e9}llo\")");
+        assert_eval_false("isalpha(\"\")");
+        assert_eval_false("isalpha(\"a1\")");
+        assert_eval_true("isalnum(\"a1\")");
+        assert_eval_true("isdigit(\"\u{661}\u{662}\u{663}\")");
+        assert_eval_false("isdigit(\"1.5\")");
+        assert_eval_true("isspace(\" \\t\")");
+        assert_eval_true("ispunct(\"!?\")");
+        assert_eval_false("ispunct(\"$\")");
+        assert_eval_true("isupper(\"ABC1\")");
+        assert_eval_false("isupper(\"AbC\")");
+        assert_eval_true("islower(\"\u{df}\")");
+        assert_eval_false("islower(\"123\")");
+        assert_eval_error("isalpha(42)");
+    }
+
+    #[test]
+    fn grapheme_mode() {
+        const TEXT: &'static str = "\"e\u{301}xy\"";
+        let eval_graphemes = |expr: &str| {
+            let mut context = Context::new();
+            context.set_graphemes(true);
+            rush::eval(&expr.replace("TEXT", TEXT), &mut context).unwrap()
+        };
+        let string = |s: &str| Value::String(s.to_owned());
+
+        assert_eq!(Value::Integer(3), eval_graphemes("len(TEXT)"));
+        assert_eq!(string("x"), eval_graphemes("TEXT[1]"));
+        assert_eq!(string("e\u{301}"), eval_graphemes("TEXT[-3]"));
+        assert_eq!(string("e\u{301}x"), eval_graphemes("TEXT[0:2]"));
+
+        // by default, combining characters are counted separately
+        assert_eq!("4", eval(&"len(TEXT)".replace("TEXT", TEXT)));
+        assert_eq!("\u{301}", eval(&"TEXT[1]".replace("TEXT", TEXT)));
+    }
+}
+
+mod layout {
+    use util::*;
+
+    #[test]
+    fn padding() {
+        assert_eq!("   ab", eval("lpad(5, ab)"));
+        assert_eq!("ab   ", eval("rpad(5, ab)"));
+        assert_eq!("  ab  ", eval("center(6, ab)"));
+        assert_eq!(" ab  ", eval("center(5, ab)"));
+        assert_eq!("abc", eval("lpad(2, abc)"));
+        assert_eq!("   \u{6f22}", eval("lpad(5, \"\u{6f22}\")"));
+        assert_eq!("e\u{301}  ", eval("rpad(3, \"e\u{301}\")"));
+        assert_eval_error("lpad(-1, a)");
+        assert_eval_error("rpad(a, b)");
+        assert_eval_error("center(5, 42)");
+    }
+
+    #[test]
+    fn wrap() {
+        assert_eq!(unlines!("the quick", "brown fox", "jumps"),
+                   eval("wrap(10, \"the quick brown fox jumps\")"));
+        assert_eq!("abcdef\ngh", eval("wrap(3, \"abcdef gh\")"));
+        assert_eq!("a b\nc", eval("wrap(20, \"a b\\nc\")"));
+        assert_eq!("", eval("wrap(5, \"\")"));
+        assert_eval_error("wrap(-5, foo)");
+    }
+
+    #[test]
+    fn truncate() {
+        assert_eq!("hello...", eval("truncate(8, \"...\", \"hello world\")"));
+        assert_eq!("hello", eval("truncate(20, \"...\", hello)"));
+        assert_eq!("hell", eval("truncate(4, \"\", hello)"));
+        assert_eq!("\u{6f22}\u{5b57}\u{2026}",
+                   eval("truncate(5, \"\u{2026}\", \"\u{6f22}\u{5b57}\u{6f22}\u{5b57}\")"));
+        assert_eval_error("truncate(2, \"...\", hello)");
+        assert_eval_error("truncate(2, hello)");
+    }
+
+    #[test]
+    fn indentation() {
+        assert_eq!("  a\n\n  b", eval("indent(2, \"a\\n\\nb\")"));
+        assert_eq!("> a\n> b", eval("indent(\"> \", \"a\\nb\")"));
+        assert_eval_error("indent([], a)");
+
+        assert_eq!("a\n  b\nc", eval("dedent(\"  a\\n    b\\n  c\")"));
+        assert_eq!("a\n\nb", eval("dedent(\"  a\\n   \\n  b\")"));
+        assert_eq!("a\n b", eval("dedent(\"a\\n b\")"));
+        assert_eval_error("dedent(42)");
+    }
+
+    #[test]
+    fn table_plain() {
+        assert_eq!("", eval("table([])"));
+        assert_eq!("a     1\nbbb  22", eval("table([[a, 1], [bbb, 22]])"));
+        assert_eq!(unlines!("name   n", "----  --", "x      1", "yy    10"),
+                   eval("table([{name: x, n: 1}, {name: yy, n: 10}])"));
+        assert_eq!(unlines!("a  b", "-  -", "1", "   2"),
+                   eval("table([{a: 1}, {b: 2}])"));
+        assert_eval_error("table([1, 2])");
+        assert_eval_error("table(foo)");
+        assert_eval_error("table([[1]], html)");
+    }
+
+    #[test]
+    fn table_markdown() {
+        assert_eq!(unlines!("|   a | b   |", "| --: | --- |", "|   1 | x   |"),
+                   eval("table([{a: 1, b: x}], markdown)"));
+        assert_eq!("| a\\|b |\n| ---- |", eval("table([[\"a|b\"]], md)"));
+    }
+}
@@ -0,0 +1,64 @@
+//! Tests for the date & time API functions.
+
+use util::*;
+
+
+#[test]
+fn strptime() {
+    assert_eq!("2016-10-18T00:00:00+00:00",
+               eval("strptime(\"%Y-%m-%d\", \"2016-10-18\")"));
+    assert_eq!("2016-10-18T12:34:56+00:00",
+               eval("strptime(\"%d/%m/%Y %H:%M:%S\", \"18/10/2016 12:34:56\")"));
+    assert_eq!("2016-10-18T12:34:56+02:00",
+               eval("strptime(\"%Y-%m-%d %H:%M:%S %z\", \"2016-10-18 12:34:56 +0200\")"));
+    assert_eval_error("strptime(\"%Y-%m-%d\", foo)");
+    assert_eval_error("strptime(\"%Y-%m-%d\", 42)");
+    assert_eval_error("strptime(42, \"2016-10-18\")");
+}
+
+#[test]
+fn strftime() {
+    assert_eq!("2016-10-18", eval("strftime(\"%Y-%m-%d\", fromepoch(1476748800))"));
+    assert_eq!("00:00", eval("strftime(\"%H:%M\", fromepoch(0))"));
+    assert_eval_error("strftime(\"%Y\", \"2016\")");
+    assert_eval_error("strftime(\"%Y\", 42)");
+    assert_eval_error("strftime(\"%Q\", now())");
+    assert_eval_error("strftime(\"%Y-%\", now())");
+}
+
+#[test]
+fn epoch() {
+    assert_eq!("0", eval("epoch(fromepoch(0))"));
+    assert_eq!("86400", eval("epoch(strptime(\"%Y-%m-%d\", \"1970-01-02\"))"));
+    assert_eq!("1.5", eval("epoch(fromepoch(1.5))"));
+    assert_eq!("1970-01-01T00:00:00+00:00", eval("fromepoch(0)"));
+    assert_eval_error("epoch(42)");
+    assert_eval_error("fromepoch(foo)");
+}
+
+#[test]
+fn tz() {
+    assert_eq!("01:00", eval("strftime(\"%H:%M\", tz(\"Europe/Warsaw\", fromepoch(0)))"));
+    assert_eq!("1970-01-01T01:00:00+01:00", eval("tz(3600, fromepoch(0))"));
+    assert_eq!("0", eval("epoch(tz(\"Asia/Tokyo\", fromepoch(0)))"));
+    assert_eval_error("tz(\"Nowhere/Special\", fromepoch(0))");
+}
+
+#[test]
+fn comparison() {
+    assert_eval_true("fromepoch(1) > fromepoch(0)");
+    assert_eval_true("fromepoch(0) < fromepoch(1)");
+    assert_eval_true("fromepoch(0) == tz(3600, fromepoch(0))");
+    assert_eval_error("fromepoch(0) < 0");
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!("120", eval("epoch(fromepoch(100) + 20)"));
+    assert_eq!("120", eval("epoch(20 + fromepoch(100))"));
+    assert_eq!("80", eval("epoch(fromepoch(100) - 20)"));
+    assert_eq!("60", eval("fromepoch(100) - fromepoch(40)"));
+    assert_eq!("-0.5", eval("fromepoch(1) - fromepoch(1.5)"));
+    assert_eval_error("20 - fromepoch(100)");
+    assert_eval_error("fromepoch(100) + fromepoch(40)");
+}
@@ -0,0 +1,58 @@
+//! Tests for the URL functions.
+
+use util::*;
+
+
+#[test]
+fn url() {
+    const URL: &'static str = "\"https://joe@example.com:8080/a/b?x=1&y=a%20b&x=3#top\"";
+    assert_eq!("https", eval(&format!("url({})[\"scheme\"]", URL)));
+    assert_eq!("joe", eval(&format!("url({})[\"user\"]", URL)));
+    assert_eq!("example.com", eval(&format!("url({})[\"host\"]", URL)));
+    assert_eq!("8080", eval(&format!("url({})[\"port\"]", URL)));
+    assert_eq!("/a/b", eval(&format!("url({})[\"path\"]", URL)));
+    assert_eq!(r#"{"x":["1","3"],"y":"a b"}"#, eval(&format!("url({})[\"query\"]", URL)));
+    assert_eq!("top", eval(&format!("url({})[\"fragment\"]", URL)));
+}
+
+#[test]
+fn url_partial() {
+    assert_eq!("/index.html", eval("url(\"/index.html?q=1\")[\"path\"]"));
+    assert_eval_true("url(\"/index.html?q=1\")[\"host\"] == nil");
+    assert_eq!("{}", eval("url(\"http://example.com\")[\"query\"]"));
+    assert_eq!("", eval("url(\"http://example.com\")[\"path\"]"));
+    assert_eq!("[::1]", eval("url(\"http://[::1]:80/\")[\"host\"]"));
+    assert_eq!("mailto", eval("url(\"mailto:joe@example.com\")[\"scheme\"]"));
+    assert_eval_error("url(\"http://example.com:http/\")");
+    assert_eval_error("url(42)");
+}
+
+#[test]
+fn urlbuild() {
+    assert_eq!("https://example.com:8080/a?x=1&x=2#top",
+               eval("urlbuild({scheme: \"https\", host: \"example.com\", port: 8080, \
+                               path: \"/a\", query: {x: [1, 2]}, fragment: \"top\"})"));
+    assert_eq!("/search?q=a%20b", eval("urlbuild({path: \"/search\", query: \"q=a%20b\"})"));
+    assert_eq!("http://joe@example.com/?a=1",
+               eval("urlbuild(url(\"http://joe@example.com/?a=1\"))"));
+    assert_eval_error("urlbuild({hostname: \"example.com\"})");
+    assert_eval_error("urlbuild(\"http://example.com\")");
+}
+
+#[test]
+fn querystring() {
+    assert_eq!("a=1&b=x%26y&c=true", eval("querystring({a: 1, b: \"x&y\", c: true})"));
+    assert_eq!("k=1&k=2", eval("querystring({k: [1, 2], n: nil})"));
+    assert_eq!("", eval("querystring({})"));
+    assert_eval_error("querystring({a: {b: 1}})");
+    assert_eval_error("querystring(\"a=1\")");
+}
+
+#[test]
+fn fromquerystring() {
+    assert_eq!(r#"{"a":"1","b":"x y"}"#, eval("fromquerystring(\"a=1&b=x+y\")"));
+    assert_eq!(r#"{"a":["1","2"],"flag":""}"#, eval("fromquerystring(\"?a=1&flag&a=2\")"));
+    assert_eq!("{}", eval("fromquerystring(\"\")"));
+    assert_eval_error("fromquerystring(\"a=%zz\")");
+    assert_eval_error("fromquerystring({})");
+}
@@ -21,6 +21,20 @@ mod numbers {
         assert_noop_eval("42");
     }
 
+    #[test]
+    fn integer_prefixed() {
+        assert_eq!("31", eval("0x1F"));
+        assert_eq!("31", eval("0X1f"));
+        assert_eq!("15", eval("0o17"));
+        assert_eq!("5", eval("0b101"));
+    }
+
+    #[test]
+    fn integer_underscores() {
+        assert_eq!("1000000", eval("1_000_000"));
+        assert_eq!("65535", eval("0xFF_FF"));
+    }
+
     #[test]
     fn integer_negative() {
         // Note that this may actually be interpreted as unary minus expression,
@@ -50,6 +64,18 @@ mod numbers {
         assert_eq!(expected, eval(EXPR));
     }
 
+    #[test]
+    fn float_scientific_integral() {
+        assert_eq!("100000.0", eval("1e5"));
+        assert_eq!("0.025", eval("25E-3"));
+    }
+
+    #[test]
+    fn float_leading_dot() {
+        assert_eq!("0.5", eval(".5"));
+        assert_eq!("1500.0", eval(".15e4"));
+    }
+
     #[test]
     fn float_negative() {
         // Note that this may actually be interpreted as unary minus expression,
@@ -76,6 +102,43 @@ mod string {
     }
 }
 
+mod regex {
+    use util::*;
+
+    #[test]
+    fn slashes() {
+        assert_eval_true(r#"foo @ /o+/"#);
+        assert_eval_true(r#""a/b" @ /a[/]b/"#);
+        assert_eval_true(r#""a/b" @ /a\/b/"#);
+        assert_eval_true(r#""/usr/bin" @ /^\/usr\/\w+$/"#);
+        assert_eval_false(r#"abc @ /a\/b/"#);
+        assert_eval_error(r#"foo @ /(/"#);
+    }
+
+    #[test]
+    fn flags() {
+        assert_eval_false(r#"FOO @ /foo/"#);
+        assert_eval_true(r#"FOO @ /foo/i"#);
+        assert_eval_true(r#""a\nb" @ /^b$/m"#);
+        assert_eval_false(r#""a\nb" @ /^b$/"#);
+        assert_eval_true(r#""a\nb" @ /a.b/s"#);
+        assert_eval_true(r#"foo @ /f o o/x"#);
+        assert_eval_true(r#""a\nB" @ /A.b/is"#);
+    }
+
+    #[test]
+    fn alternative_delimiters() {
+        assert_eval_true(r#""/usr/bin" @ r"^/usr/\w+$""#);
+        assert_eval_true(r#""say \"hi\"" @ r"\"hi\"""#);
+        assert_eval_true(r#""/usr/bin" @ m{^/usr/\w{3}$}"#);
+        assert_eval_true(r#""a{b}" @ m{a\{b\}}"#);
+        assert_eval_true(r#"FOO @ r"foo"i"#);
+        assert_eval_true(r#"FOO @ m{foo}i"#);
+        assert_eq!("m", eval("m"));
+        assert_eq!("r", eval("r"));
+    }
+}
+
 mod array {
     use util::*;
 
@@ -159,6 +222,23 @@ mod object {
         assert_eq!(elems, actual);
     }
 
+    #[test]
+    fn key_order() {
+        assert_noop_eval(r#"{"b":1,"a":2,"c":3}"#);
+        assert_eq!(r#"{"a":3,"b":2}"#, eval("{a: 1, b: 2, a: 3}"));
+    }
+
+    #[test]
+    fn builtin_names_as_keys() {
+        assert_eq!(r#"{"e":1}"#, eval("{e: 1}"));
+        assert_eq!(r#"{"log":1}"#, eval("{log: 1}"));
+        assert_eq!(r#"{"pi":1,"url":2}"#, eval("{pi: 1, url: 2}"));
+        assert_eq!("foo", eval("{url: foo}[url]"));
+        assert_eq!("2", eval("{log: 1, mean: 2}[mean]"));
+        assert_eq!("http://example.com",
+                   apply("_[url]", r#"{"url": "http://example.com"}"#));
+    }
+
     #[test]
     fn duplicate_key() {
         let key = "a";
@@ -0,0 +1,262 @@
+//! Tests for user-defined functions (lambdas).
+
+use util::*;
+
+
+#[test]
+fn call() {
+    assert_eq!("42", eval("(|| 42)()"));
+    assert_eq!("4", eval("(|x| x + 1)(3)"));
+    assert_eq!("7", eval("(|x, y| x + y)(3, 4)"));
+    assert_eval_error("(|x| x)(1, 2)");
+}
+
+mod closures {
+    use rush::{self, Context, Value};
+    use util::*;
+
+    #[test]
+    fn capture_argument() {
+        assert_eq!("8", eval("(|n| |x| x + n)(5)(3)"));
+        assert_eq!(unlines!("3", "6", "9"), eval("map((|n| |x| x * n)(3), [1, 2, 3])"));
+        assert_eq!("foobar", eval("(|a| |b| |c| a + b + c)(f)(oo)(bar)"));
+    }
+
+    #[test]
+    fn capture_local_variable() {
+        assert_eq!("12", eval("(|x| (|y| y + x)(2))(10)"));
+        assert_eq!(unlines!("11", "12"), eval("(|n| map(|x| x + n, [1, 2]))(10)"));
+    }
+
+    #[test]
+    fn lexical_not_dynamic() {
+        // `n` in the caller's scope doesn't affect the `n` captured by the closure
+        assert_eq!("11", eval("(|adder| (|n| adder(1)(n))(10))(|n| |x| x + n)"));
+        assert_eq!("0", eval("(|f| (|x| f())(1))((|x| || x)(0))"));
+    }
+
+    #[test]
+    fn caller_variables_invisible() {
+        assert_eval_error("(|f| (|y| f())(1))(|| y + 1)");
+    }
+
+    #[test]
+    fn globals_visible() {
+        let mut context = Context::new();
+        rush::exec("adder = |n| |x| x + n", &mut context).unwrap();
+        rush::exec("offset = 100", &mut context).unwrap();
+        assert_eq!(Value::Integer(3), rush::eval("adder(1)(2)", &mut context).unwrap());
+        assert_eq!(Value::Integer(101),
+                   rush::eval("(|x| x + offset)(1)", &mut context).unwrap());
+        assert_eq!(Value::Integer(105),
+                   rush::eval("map(|x| adder(offset)(x), [5])[0]", &mut context).unwrap());
+    }
+
+    #[test]
+    fn captured_by_value() {
+        let mut context = Context::new();
+        rush::exec("f = (|n| |x| x + n)(1)", &mut context).unwrap();
+        rush::exec("n = 100", &mut context).unwrap();
+        assert_eq!(Value::Integer(2), rush::eval("f(1)", &mut context).unwrap());
+    }
+}
+
+mod currying {
+    use util::*;
+
+    #[test]
+    fn partial_application() {
+        assert_eq!("5", eval("(|x, y| x - y)(8)(3)"));
+        assert_eq!("5", eval("(|x, y| x - y) $ 8 $ 3"));
+        assert_eq!("abc", eval("(|a, b, c| a + b + c)(a)(b, c)"));
+    }
+
+    #[test]
+    fn of_closure() {
+        assert_eq!("111", eval("(|n| |x, y| x + y + n)(100)(10)(1)"));
+        assert_eq!(unlines!("101", "102"), eval("map((|n| |x, y| x + y + n)(100)(1), [0, 1])"));
+    }
+}
+
+mod composition {
+    use util::*;
+
+    #[test]
+    fn amp_operator() {
+        // f & g applies f first, then g
+        assert_eq!("11", eval("(|x| x * 2) & (|x| x + 1) $ 5"));
+        assert_eq!("12", eval("(|x| x + 1) & (|x| x * 2) $ 5"));
+        assert_eval_error("(|x| x) & (|x, y| x)");
+    }
+
+    #[test]
+    fn of_closures() {
+        assert_eq!("11", eval("(|n| |x| x * n)(2) & (|n| |x| x + n)(1) $ 5"));
+        assert_eq!(unlines!("3", "5"),
+                   eval("map((|n| |x| x * n)(2) & (|n| |x| x + n)(1), [1, 2])"));
+    }
+
+    #[test]
+    fn star_operator() {
+        // f * g applies g first, then f
+        assert_eq!("12", eval("(|x| x * 2) * (|x| x + 1) $ 5"));
+        assert_eq!("7", eval("(|n| |x| x + n)(1) * (|n| |x| x * n)(3) $ 2"));
+    }
+}
+
+mod definitions {
+    use rush::{self, Context, Value};
+
+    #[test]
+    fn simple() {
+        let mut context = Context::new();
+        assert_eq!(Value::Empty, rush::eval("def inc(x) = x + 1", &mut context).unwrap());
+        assert_eq!(Value::Integer(43), rush::eval("inc(42)", &mut context).unwrap());
+        rush::exec("def answer() = 42", &mut context).unwrap();
+        assert_eq!(Value::Integer(42), rush::eval("answer()", &mut context).unwrap());
+        rush::exec("def adder(n) = |x| x + n", &mut context).unwrap();
+        assert_eq!(Value::Integer(5), rush::eval("adder(2)(3)", &mut context).unwrap());
+    }
+
+    #[test]
+    fn keyword_is_reserved() {
+        assert!(rush::eval("def", &mut Context::new()).is_err());
+        assert_eq!(Value::String("define".to_owned()),
+                   rush::eval("define", &mut Context::new()).unwrap());
+    }
+
+    #[test]
+    fn recursion() {
+        let mut context = Context::new();
+        rush::exec("def fact(n) = n <= 1 ? 1 : n * fact(n - 1)", &mut context).unwrap();
+        assert_eq!(Value::Integer(1), rush::eval("fact(0)", &mut context).unwrap());
+        assert_eq!(Value::Integer(3628800), rush::eval("fact(10)", &mut context).unwrap());
+        assert_eq!(Value::Integer(120),
+                   rush::eval("map(fact, [5])[0]", &mut context).unwrap());
+    }
+
+    #[test]
+    fn recursion_without_global_name() {
+        let mut context = Context::new();
+        rush::exec("def fact(n) = n <= 1 ? 1 : n * fact(n - 1)", &mut context).unwrap();
+        rush::exec("f = fact", &mut context).unwrap();
+        rush::exec("fact = 0", &mut context).unwrap();
+        assert_eq!(Value::Integer(120), rush::eval("f(5)", &mut context).unwrap());
+    }
+
+    #[test]
+    fn mutual_recursion() {
+        let mut context = Context::new();
+        rush::exec("def even(n) = n == 0 ? true : odd(n - 1)", &mut context).unwrap();
+        rush::exec("def odd(n) = n == 0 ? false : even(n - 1)", &mut context).unwrap();
+        assert_eq!(Value::Boolean(true), rush::eval("even(10)", &mut context).unwrap());
+        assert_eq!(Value::Boolean(false), rush::eval("odd(10)", &mut context).unwrap());
+    }
+
+    #[test]
+    fn tree_walking() {
+        let mut context = Context::new();
+        rush::exec("def total(t) = fold(|acc, c| acc + total(c), t[children], t[v])",
+                   &mut context).unwrap();
+        let tree = "{v: 1, children: [{v: 2, children: []}, \
+                                      {v: 3, children: [{v: 4, children: []}]}]}";
+        assert_eq!(Value::Integer(10),
+                   rush::eval(&format!("total({})", tree), &mut context).unwrap());
+    }
+
+    #[test]
+    fn depth_limit() {
+        let mut context = Context::new();
+        context.set_max_depth(50);
+        rush::exec("def down(n) = n == 0 ? 0 : 1 + down(n - 1)", &mut context).unwrap();
+        assert_eq!(Value::Integer(40), rush::eval("down(40)", &mut context).unwrap());
+        let error = rush::eval("down(100)", &mut context).unwrap_err();
+        assert!(format!("{}", error).contains("recursion depth"));
+    }
+
+    #[test]
+    fn tail_calls() {
+        let mut context = Context::new();
+        context.set_max_depth(50);
+        rush::exec("def count(n, acc) = n == 0 ? acc : count(n - 1, acc + 1)",
+                   &mut context).unwrap();
+        assert_eq!(Value::Integer(10000), rush::eval("count(10000, 0)", &mut context).unwrap());
+    }
+}
+
+mod parameters {
+    use rush::{self, Context, Value};
+    use util::*;
+
+    #[test]
+    fn defaults() {
+        assert_eq!("a,", eval(r#"(|x, sep=","| x + sep)(a)"#));
+        assert_eq!("a;", eval(r#"(|x, sep=","| x + sep)(a, ";")"#));
+        assert_eq!("-1", eval("(|x=-1| x)()"));
+        assert_eq!("6", eval("(|x, y=2, z=3| x + y + z)(1)"));
+        assert_eval_error("(|x, sep=\",\"| x)(a, b, c)");
+        assert_eval_error("(|x=1, y| x)(1, 2)");  // required param after default
+    }
+
+    #[test]
+    fn defaults_evaluated_at_definition() {
+        let mut context = Context::new();
+        rush::exec("d = 1", &mut context).unwrap();
+        rush::exec("f = |x, y=d| x + y", &mut context).unwrap();
+        rush::exec("d = 100", &mut context).unwrap();
+        assert_eq!(Value::Integer(2), rush::eval("f(1)", &mut context).unwrap());
+    }
+
+    #[test]
+    fn rest() {
+        assert_eq!("", eval("(|*xs| xs)()"));
+        assert_eq!(unlines!("1", "2", "3"), eval("(|*xs| xs)(1, 2, 3)"));
+        assert_eq!("1", eval("(|x, *xs| x)(1, 2, 3)"));
+        assert_eq!(unlines!("2", "3"), eval("(|x, *xs| xs)(1, 2, 3)"));
+        assert_eq!("0", eval("(|x, *xs| len(xs))(1)"));
+        assert_eq!("2", eval("(|x, y=1, *xs| len(xs) + y)(1, 2)"));
+        assert_eq!("4", eval("(|x, y=1, *xs| len(xs) + y)(1, 2, 3, 4)"));
+        assert_eval_error("(|x, *xs| x)()");
+        assert_eval_error("(|*xs, x| x)(1)");  // rest param must be last
+    }
+
+    #[test]
+    fn keyword_arguments() {
+        assert_eq!("a;", eval(r#"(|x, sep=","| x + sep)(a, sep=";")"#));
+        assert_eq!("a;", eval(r#"(|x, sep=","| x + sep)(x=a, sep=";")"#));
+        assert_eq!("ba", eval("(|x, y| x + y)(y=a, x=b)"));
+        assert_eq!("ab", eval("(|x, y| x + y)(b, x=a)"));  // positional fills the rest
+        assert_eq!("true", eval("(|x| x)(x = 1 == 1)"));
+        assert_eval_error("(|x| x)(y=1)");
+        assert_eval_error("(|x| x)(x=1, x=2)");
+        assert_eval_error("(|x, y| x)(x=1, 2)");  // positional after keyword
+        assert_eval_error("abs(x=1)");  // native functions don't take them
+    }
+
+    #[test]
+    fn keyword_arguments_with_def() {
+        let mut context = Context::new();
+        rush::exec(r#"def split_on(s, by=",") = split(by, s)"#, &mut context).unwrap();
+        assert_eq!(Value::Integer(3),
+                   rush::eval(r#"len(split_on("a b c", by=" "))"#, &mut context).unwrap());
+        assert_eq!(Value::Integer(2),
+                   rush::eval(r#"len(split_on("a,b"))"#, &mut context).unwrap());
+    }
+
+    #[test]
+    fn currying() {
+        // functions are curried when not given all the required arguments...
+        assert_eq!("a;", eval(r#"(|x, sep=","| x + sep)()(a, ";")"#));
+        assert_eq!(unlines!("a;", "b;"), eval(r#"map((|sep, x| x + sep)(";"), [a, b])"#));
+        assert_eq!(unlines!("1", "2"), eval("map((|x, *xs| x)(), [1, 2])"));
+        // ...including when keyword arguments are given instead
+        assert_eq!(unlines!("a;", "b;"), eval(r#"map((|x, sep| x + sep)(sep=";"), [a, b])"#));
+        assert_eq!(unlines!("a;", "b;"), eval(r#"map((|sep, x| x + sep)(sep=";"), [a, b])"#));
+    }
+
+    #[test]
+    fn composition() {
+        assert_eq!("a,", eval(r#"(|x| x) & (|x, sep=","| x + sep) $ a"#));
+        assert_eq!("a;", eval(r#"(|x| x) & (|x, sep=","| x + sep)(sep=";") $ a"#));
+    }
+}
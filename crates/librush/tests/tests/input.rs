@@ -0,0 +1,174 @@
+//! Tests for the variables that hold the input.
+
+use util::*;
+
+
+#[test]
+fn current() {
+    assert_eq!("42", apply("_", "42"));
+    assert_eq!("7", apply("_", "007"));
+    assert_eq!("foo", apply("_", "foo"));
+}
+
+#[test]
+fn raw_string() {
+    assert_eq!("007", apply("_s", "007"));
+    assert_eq!("5", apply("len(_s)", "02134"));
+    assert_eq!("1.50", apply("_s", "1.50"));
+    assert_eq!("foo", apply("_s", "foo"));
+}
+
+#[test]
+fn integer() {
+    assert_eq!("8", apply("_i + 1", "007"));
+    assert_eq!("-3", apply("_i", "-3"));
+    assert_apply_error("_i", "foo");
+    assert_apply_error("_i", "1.5");
+}
+
+#[test]
+fn float() {
+    assert_eq!("3.0", apply("_f", "3"));
+    assert_eq!("3.0", apply("_f * 2", "1.5"));
+    assert_apply_error("_f", "foo");
+}
+
+#[test]
+fn boolean() {
+    assert_eq!("true", apply("_b", "true"));
+    assert_eq!("true", apply("!_b", "false"));
+    assert_apply_error("_b", "foo");
+}
+
+#[test]
+fn in_lambda() {
+    assert_eq!("8", apply("(|x| x + _i)(1)", "007"));
+}
+
+#[test]
+fn lines() {
+    assert_eq!("1", apply_lines("_[0]", &["01", "02"]));
+    assert_eq!("01", apply_lines("_s[0]", &["01", "02"]));
+    assert_eq!("3", apply_lines("fold(|a, x| a + x, _i, 0)", &["01", "02"]));
+    assert_apply_lines_error("_i", &["1", "foo"]);
+}
+
+
+mod coercion {
+    use rush::{self, Context};
+    use rush::value::Coercion;
+
+    #[test]
+    fn raw() {
+        let input = "007\n1e3\n1.50\ntrue\nfoo\n";
+        assert_eq!(input, map_lines_with("_", input, Coercion::none()));
+        assert_eq!("3\n3\n4\n4\n3\n", map_lines_with("len(_)", input, Coercion::none()));
+    }
+
+    #[test]
+    fn selected() {
+        let input = "007\n1.50\ntrue\n";
+        let ints = "int".parse::<Coercion>().unwrap();
+        assert_eq!("7\n1.50\ntrue\n", map_lines_with("_", input, ints));
+        let floats_and_bools = "float,bool".parse::<Coercion>().unwrap();
+        assert_eq!("7.0\n1.5\ntrue\n", map_lines_with("_", input, floats_and_bools));
+    }
+
+    #[test]
+    fn radix() {
+        let input = "0x1F\n0o17\n0b101\n1_000\nfoo\n";
+        assert_eq!(input, map_lines_with("_", input, Coercion::all()));
+        let radix = "int,radix".parse::<Coercion>().unwrap();
+        assert_eq!("31\n15\n5\n1000\nfoo\n", map_lines_with("_", input, radix));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Coercion::all(), "int,float,bool".parse().unwrap());
+        assert_eq!(Coercion::none(), "".parse().unwrap());
+        assert!("int,string".parse::<Coercion>().is_err());
+    }
+
+    #[test]
+    fn identity() {
+        for input in &["a\nb\n", "a\r\nb\r\n", "a\nb", "\n\n", ""] {
+            assert_eq!(*input, map_lines_verbatim("_", input));
+        }
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!("a\nb\n", map_lines_with("_", "a\r\nb", Coercion::none()));
+        assert_eq!("1\n1\n", map_lines_with("len(_)", "x\r\ny", Coercion::none()));
+    }
+
+    fn map_lines_verbatim(expr: &str, input: &str) -> String {
+        let mut context = Context::new();
+        context.set_coercion(Coercion::none());
+        context.set_keep_line_endings(true);
+
+        let mut output: Vec<u8> = Vec::new();
+        rush::map_lines_multi_ctx(&mut context, &[expr], input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn map_lines_with(expr: &str, input: &str, coercion: Coercion) -> String {
+        let mut context = Context::new();
+        context.set_coercion(coercion);
+
+        let mut output: Vec<u8> = Vec::new();
+        rush::map_lines_multi_ctx(&mut context, &[expr], input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+}
+
+
+mod metadata {
+    use std::env;
+    use std::fs::File;
+    use std::io::Write;
+    use std::str::from_utf8;
+
+    use rush;
+    use util::*;
+
+    #[test]
+    fn lines() {
+        assert_eq!(unlines!("0", "1", "2", ""), map_lines("_n", unlines!("a", "b", "c", "")));
+        assert_eq!(unlines!("1", "2", "3", ""), map_lines("_l", unlines!("a", "b", "c", "")));
+        assert_eq!(unlines!("0", "4", "6", ""), map_lines("_o", unlines!("foo", "x", "bar", "")));
+        assert_eq!("0\n5\n", map_lines("_o", "foo\r\nbar\n"));
+    }
+
+    #[test]
+    fn words() {
+        let output = map_words("join(\",\", [_n, _l, _o])", "foo bar\n  baz");
+        assert_eq!("0,1,0 1,1,4\n  2,2,10\n", output);
+    }
+
+    #[test]
+    fn files() {
+        let dir = env::temp_dir();
+        let paths: Vec<_> = ["rush_metadata_a.txt", "rush_metadata_b.txt"].iter()
+            .map(|name| dir.join(name)).collect();
+        for path in &paths {
+            File::create(path).unwrap().write_all(b"content").unwrap();
+        }
+
+        let input = paths.iter()
+            .map(|p| p.to_str().unwrap().to_owned()).collect::<Vec<_>>().join("\n");
+        let mut output: Vec<u8> = Vec::new();
+        rush::map_files("join(\":\", [_n, _l, _o, _p])", input.as_bytes(), &mut output).unwrap();
+
+        let first = paths[0].to_str().unwrap();
+        let expected = format!("0:1:0:{}\n1:2:{}:{}\n",
+                               first, first.len() + 1, paths[1].to_str().unwrap());
+        assert_eq!(expected, from_utf8(&output).unwrap());
+    }
+
+    fn map_words(expr: &str, input: &str) -> String {
+        let mut output: Vec<u8> = Vec::new();
+        rush::map_words(expr, input.as_bytes(), &mut output).unwrap();
+        from_utf8(&output).unwrap().to_owned()
+    }
+}
@@ -2,7 +2,11 @@
 
 mod api;
 mod constants;
+mod functions;
+mod input;
 mod operators;
+mod output;
+mod strict;
 mod trailers;
 
 
@@ -108,6 +108,10 @@ mod equal {
         assert_eval_true("4 == 4.0");
         assert_eval_true("[] == []");
         assert_eval_true("{} == {}");
+        assert_eval_true("nil == nil");
+        assert_eval_false("nil == 0");
+        assert_eval_false("foo == nil");
+        assert_eval_false("[] == nil");
         assert_eval_error("0 == foo");
         assert_eval_error("foo == 42");
         assert_eval_error("bar == true");
@@ -133,9 +137,26 @@ mod not_equal {
         assert_eval_false("4 != 4.0");
         assert_eval_false("[] != []");
         assert_eval_false("{} != {}");
+        assert_eval_false("nil != nil");
+        assert_eval_true("false != nil");
         assert_eval_error("0 != foo");
         assert_eval_error("foo != 42");
         assert_eval_error("bar != true");
     }
     // TODO(xion): inputs
 }
+
+mod match_ {
+    use util::*;
+
+    #[test]
+    fn constants() {
+        assert_eval_true(r#"bool(foo =~ /o+/)"#);
+        assert_eval_false(r#"bool(foo =~ /x/)"#);
+        assert_eq!("oo", eval(r#"(foo =~ /f(o+)/)["1"]"#));
+        assert_eq!("bar", eval(r#"("foo=bar" =~ /=(?P<v>\w+)/)["v"]"#));
+        assert_eval_true(r#"(foo =~ /x/) == nil"#);
+        assert_eval_error(r#"/o+/ =~ foo"#);
+        assert_eval_error(r#"42 =~ /4/"#);
+    }
+}
@@ -0,0 +1,66 @@
+//! Tests for how the results of expressions are output.
+
+use rush;
+use util::*;
+
+
+#[test]
+fn nil_skips_record() {
+    assert_eq!("", apply("nil", "foo"));
+    assert_eq!(unlines!("12", "42", ""), map_lines("_ > 10 ? _ : nil", unlines!("12", "5", "42", "")));
+    assert_eq!("", map_lines("nil", unlines!("a", "b", "")));
+}
+
+#[test]
+fn bytes() {
+    let mut output: Vec<u8> = Vec::new();
+    rush::map_bytes("255 - _", &[0u8, 1, 255][..], &mut output).unwrap();
+    assert_eq!(vec![255u8, 254, 0], output);
+    assert!(rush::map_bytes("_ + 1", &[255u8][..], &mut Vec::new()).is_err());
+}
+
+mod filter {
+    use std::str::from_utf8;
+    use rush::{self, Context};
+
+    #[test]
+    fn predicate() {
+        assert_eq!("foo\nfoobar\n", filter("_ @ /foo/", "foo\nbar\nfoobar\n"));
+        assert_eq!("12\n42\n", filter("_ > 10", "12\n5\n42\n"));
+        assert_eq!("", filter("false", "a\nb\n"));
+    }
+
+    #[test]
+    fn truthiness() {
+        assert_eq!("b\n", filter("_ == b ? [1] : []", "a\nb\n"));
+        assert_eq!("a\n", filter("_ == a ? 1 : nil", "a\nb\n"));
+        assert!(rush::filter_lines("_", "foo\n".as_bytes(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!("007\n", filter("_ == 7", "007\r\n8\r\n"));
+        assert_eq!("a\nb\n", filter("true", "a\nb"));
+    }
+
+    #[test]
+    fn verbatim() {
+        assert_eq!("007\r\n", filter_verbatim("_ == 7", "007\r\n8\r\n"));
+        assert_eq!("a\nb", filter_verbatim("true", "a\nb"));
+    }
+
+    fn filter(expr: &str, input: &str) -> String {
+        let mut output: Vec<u8> = Vec::new();
+        rush::filter_lines(expr, input.as_bytes(), &mut output).unwrap();
+        from_utf8(&output).unwrap().to_owned()
+    }
+
+    fn filter_verbatim(expr: &str, input: &str) -> String {
+        let mut context = Context::new();
+        context.set_keep_line_endings(true);
+
+        let mut output: Vec<u8> = Vec::new();
+        rush::filter_lines_multi_ctx(&mut context, &[expr], input.as_bytes(), &mut output).unwrap();
+        from_utf8(&output).unwrap().to_owned()
+    }
+}
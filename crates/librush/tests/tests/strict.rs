@@ -0,0 +1,66 @@
+//! Tests for the strict mode, where undefined names are errors.
+
+use std::io;
+
+use rush::{self, Context, Value};
+
+
+#[test]
+fn defined_names() {
+    assert_eq!(Value::Integer(3), strict_eval("len([1, 2, 3])").unwrap());
+    assert_eq!(Value::Integer(2), strict_eval("(|x| x + 1)(1)").unwrap());
+
+    let mut context = strict_context();
+    rush::exec("x = 42", &mut context).unwrap();
+    assert_eq!(Value::Integer(42), rush::eval("x", &mut context).unwrap());
+    assert_eq!(Value::String("foo".to_owned()), strict_eval("\"foo\"").unwrap());
+}
+
+#[test]
+fn undefined_names() {
+    assert!(strict_eval("foo").is_err());
+    assert!(strict_eval("foo + 1").is_err());
+    assert!(strict_eval("has(a, {a: 1})").is_err());
+    assert!(strict_eval("(|x| y)(1)").is_err());
+
+    // without strict mode, undefined names are strings
+    let mut context = Context::new();
+    assert_eq!(Value::String("foo".to_owned()), rush::eval("foo", &mut context).unwrap());
+}
+
+#[test]
+fn suggestions() {
+    let message = strict_eval("lenn([1, 2])").unwrap_err().to_string();
+    assert!(message.contains("`len`"), "no suggestion in: {}", message);
+
+    let mut context = strict_context();
+    rush::exec("name = 42", &mut context).unwrap();
+    let message = rush::eval("nmae", &mut context).unwrap_err().to_string();
+    assert!(message.contains("`name`"), "no suggestion in: {}", message);
+}
+
+#[test]
+fn keys() {
+    assert_eq!(Value::Integer(1), strict_eval("{a: 1}[a]").unwrap());
+    assert_eq!(Value::Integer(1), strict_eval("{a: 1}[\"a\"]").unwrap());
+    assert!(strict_eval("[1, 2][a]").is_err());
+
+    // variables are still used as keys if defined
+    let mut context = strict_context();
+    assert!(rush::exec("k = b", &mut context).is_err());
+    rush::exec("k = \"b\"", &mut context).unwrap();
+    assert_eq!(Value::Integer(2), rush::eval("{a: 1, b: 2}[k]", &mut context).unwrap());
+}
+
+
+// Utility functions
+
+fn strict_context<'c>() -> Context<'c> {
+    let mut context = Context::new();
+    context.set_strict(true);
+    context
+}
+
+fn strict_eval(expr: &str) -> io::Result<Value> {
+    rush::eval(expr, &mut strict_context())
+}
@@ -48,6 +48,15 @@ mod subscript {
             assert_apply_error("_[42]", INPUT);
             assert_apply_error("_[-42]", INPUT);
         }
+
+        #[test]
+        fn non_ascii() {
+            const INPUT: &'static str = "za\u{17c}\u{f3}\u{142}\u{107}";
+            assert_eq!("\u{17c}", apply("_[2]", INPUT));
+            assert_eq!("\u{107}", apply("_[-1]", INPUT));
+            assert_eq!("\u{f3}\u{142}", apply("_[3:5]", INPUT));
+            assert_apply_error("_[6]", INPUT);
+        }
     }
 
     // TODO(xion): tests for subscript ranges
@@ -7,6 +7,7 @@ use std::iter::IntoIterator;
 use clap::{self, AppSettings, Arg, ArgSettings, ArgGroup, ArgMatches};
 use conv::TryFrom;
 use conv::errors::Unrepresentable;
+use rush::value::Coercion;
 
 
 /// Parse command line arguments and return matches' object.
@@ -21,8 +22,33 @@ pub fn parse() -> Options {
 pub fn parse_from_argv<I, T>(argv: I) -> Options
     where I: IntoIterator<Item=T>, T: Into<OsString>
 {
+    // Arguments after the separator are paths to input files,
+    // rather than expressions that clap would otherwise take them for.
+    let mut argv: Vec<OsString> = argv.into_iter().map(Into::into).collect();
+    let files: Vec<String> = match argv.iter().position(|a| a.to_str() == Some(FILES_SEPARATOR)) {
+        Some(pos) => argv.split_off(pos).into_iter().skip(1)
+            .map(|f| f.to_string_lossy().into_owned()).collect(),
+        None => vec![],
+    };
+
+    // The backup suffix for in-place editing is an optional value of the flag,
+    // so it has to be attached with '=' in order not to be mistaken for an expression.
+    let mut backup_suffix = None;
+    for arg in argv.iter_mut() {
+        let suffix = match arg.to_str() {
+            Some(a) if a.starts_with(IN_PLACE_FLAG) && a[IN_PLACE_FLAG.len()..].starts_with('=') =>
+                String::from(&a[IN_PLACE_FLAG.len() + 1..]),
+            _ => continue,
+        };
+        backup_suffix = if suffix.is_empty() { None } else { Some(suffix) };
+        *arg = OsString::from(IN_PLACE_FLAG);
+    }
+
     let matches = create_parser().get_matches_from(argv);
-    Options::from(matches)
+    let mut options = Options::from(matches);
+    options.files.extend(files);
+    options.backup_suffix = backup_suffix;
+    options
 }
 
 
@@ -37,6 +63,16 @@ pub struct Options {
 
     /// How to interpret the input (if anyhow).
     pub input_mode: Option<InputMode>,
+    /// Paths to the input files (with "-" meaning standard input).
+    /// If empty, standard input is used.
+    pub files: Vec<String>,
+    /// Whether the expressions are predicates for filtering the input
+    /// rather than transformations of it.
+    pub filter: bool,
+    /// Whether the input files should be overwritten with the output.
+    pub in_place: bool,
+    /// Suffix for the backup copies of files edited in place, if they should be kept.
+    pub backup_suffix: Option<String>,
     /// Optional expression to execute right before processing the input.
     pub before: Option<String>,
     /// Exoressions to process the input through, one after another.
@@ -46,6 +82,16 @@ pub struct Options {
     /// Optional expression to execute right after processing the input.
     /// If defined, only its result will be printed as output.
     pub after: Option<String>,
+
+    /// Maximum depth of nested calls to user-defined functions,
+    /// if different than the default.
+    pub max_depth: Option<usize>,
+    /// Whether referring to undefined names should be an error.
+    pub strict: bool,
+    /// Types that input strings should be converted to when they look like such values.
+    pub coercion: Coercion,
+    /// Whether strings should be measured, indexed, and sliced by grapheme clusters.
+    pub graphemes: bool,
 }
 
 impl Options {
@@ -68,6 +114,18 @@ impl<'a> From<ArgMatches<'a>> for Options {
                              .values_of(ARG_EXPRESSION).unwrap()
                              .map(String::from).collect(),
             after: matches.value_of(OPT_AFTER).map(String::from),
+            max_depth: matches.value_of(OPT_MAX_DEPTH).map(|d| d.parse().unwrap()),
+            strict: matches.is_present(OPT_STRICT),
+            coercion: if matches.is_present(OPT_RAW) { Coercion::none() }
+                      else { matches.value_of(OPT_COERCE)
+                                 .map(|c| c.parse().unwrap())
+                                 .unwrap_or_else(Coercion::default) },
+            graphemes: matches.is_present(OPT_GRAPHEMES),
+            filter: matches.is_present(OPT_FILTER),
+            in_place: matches.is_present(OPT_IN_PLACE),
+            backup_suffix: None,
+            files: matches.values_of(OPT_INPUT_FILE)
+                .map(|files| files.map(String::from).collect()).unwrap_or_else(Vec::new),
             input_mode: if matches.is_present(OPT_PARSE) { None }
                         else { Some(InputMode::from(matches)) },
         }
@@ -150,7 +208,7 @@ const USAGE: &'static str = concat!("rush", " [",
     "--string | --lines | --words | --chars | --bytes | --files",
     "] ",
     "[--before <EXPRESSION>] ", "[--after <EXPRESSION>] ",
-    "<EXPRESSION> ", "[<EXPRESSION> ...]");
+    "<EXPRESSION> ", "[<EXPRESSION> ...] ", "[-- <FILE> ...]");
 
 const OPT_VERBOSE: &'static str = "verbose";
 const OPT_QUIET: &'static str = "quiet";
@@ -160,11 +218,26 @@ const INPUT_MODES: &'static [&'static str] = &[
     "string", "lines", "words", "chars", "bytes", "files",
 ];
 const OPT_PARSE: &'static str = "parse";
+const OPT_INPUT_FILE: &'static str = "in";
+const FILES_SEPARATOR: &'static str = "--";
+const OPT_FILTER: &'static str = "filter";
+const OPT_IN_PLACE: &'static str = "in_place";
+const IN_PLACE_FLAG: &'static str = "--in-place";
 
 const OPT_BEFORE: &'static str = "before";
 const ARG_EXPRESSION: &'static str = "expr";
 const OPT_AFTER: &'static str = "after";
 
+const OPT_MAX_DEPTH: &'static str = "max_depth";
+
+/// Upper bound on the --max-depth flag.
+/// Deeper recursion would need an unreasonably large stack for the evaluation thread.
+pub const MAX_DEPTH_LIMIT: usize = 10000;
+const OPT_STRICT: &'static str = "strict";
+const OPT_RAW: &'static str = "raw";
+const OPT_COERCE: &'static str = "coerce";
+const OPT_GRAPHEMES: &'static str = "graphemes";
+
 
 /// Creates the argument parser.
 fn create_parser<'p>() -> Parser<'p> {
@@ -212,6 +285,30 @@ fn create_parser<'p>() -> Parser<'p> {
             .help("Apply the expression to the content of each file (as string) \
                    whose path is given as a line of input"))
 
+        .arg(Arg::with_name(OPT_INPUT_FILE)
+            .long("in")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("File to read the input from, instead of standard input. \
+                   Can be given multiple times to process several files in sequence \
+                   (which can also be listed after --). \
+                   Compressed (gzip or zstd) files are decompressed automatically.")
+            .next_line_help(true)
+            .value_name("FILE"))
+        .arg(Arg::with_name(OPT_FILTER)
+            .long("filter")
+            .conflicts_with_all(&["string", "words", "chars", "bytes", "files"])
+            .help("Treat the expression as a predicate and only output the lines of input \
+                   for which it's true (like grep)"))
+        .arg(Arg::with_name(OPT_IN_PLACE)
+            .long("in-place")
+            .conflicts_with(OPT_AFTER)
+            .help("Edit the input files in place, replacing their content with the output. \
+                   If a suffix is given (as in --in-place=.bak), the original files \
+                   are kept as backups with that suffix appended to their names.")
+            .next_line_help(true))
+
         // TODO: add a -0 (zero) option that changes the separator byte from \n to \0;
         // this shall apply to --lines and --files input modes (as well as their output)
 
@@ -238,6 +335,38 @@ fn create_parser<'p>() -> Parser<'p> {
                    to standard output.").next_line_help(true)
             .value_name("EXPRESSION"))
 
+        .arg(Arg::with_name(OPT_MAX_DEPTH)
+            .long("max-depth")
+            .takes_value(true)
+            .validator(validate_max_depth)
+            .help("Maximum depth of nested calls to user-defined functions \
+                   (e.g. during recursion), up to 10000")
+            .value_name("N"))
+        .arg(Arg::with_name(OPT_STRICT)
+            .long("strict")
+            .help("Treat references to undefined names as errors \
+                   rather than interpreting them as strings"))
+        .arg(Arg::with_name(OPT_RAW)
+            .long("raw")
+            .conflicts_with(OPT_COERCE)
+            .help("Keep the input as strings, without converting those that look like \
+                   numbers or booleans. Same as --coerce=''"))
+        .arg(Arg::with_name(OPT_COERCE)
+            .long("coerce")
+            .takes_value(true)
+            .empty_values(true)
+            .validator(|c| c.parse::<Coercion>().map(|_| ()))
+            .help("Comma-separated list of types (int, float, bool) that the input \
+                   should be converted to if it looks like their values. \
+                   By default, all of them are used. Additionally, `radix` converts \
+                   integers like 0x1F, 0o17, or 0b101").next_line_help(true)
+            .value_name("TYPES"))
+        .arg(Arg::with_name(OPT_GRAPHEMES)
+            .long("graphemes")
+            .help("Treat strings as sequences of grapheme clusters (user-perceived characters) \
+                   rather than Unicode code points when computing their length, \
+                   indexing or slicing them"))
+
         .arg(Arg::with_name(OPT_PARSE)
             .set(ArgSettings::Hidden)
             .conflicts_with("input_group")
@@ -260,6 +389,14 @@ fn create_parser<'p>() -> Parser<'p> {
         .version_short("V")
 }
 
+fn validate_max_depth(depth: String) -> Result<(), String> {
+    let depth = try!(depth.parse::<usize>().map_err(|e| e.to_string()));
+    if depth > MAX_DEPTH_LIMIT {
+        return Err(format!("maximum depth cannot exceed {}", MAX_DEPTH_LIMIT));
+    }
+    Ok(())
+}
+
 
 
 /// Tests verifying the soundness of the above definition.
@@ -267,7 +404,8 @@ fn create_parser<'p>() -> Parser<'p> {
 mod tests {
     use case::CaseExt;
     use conv::TryFrom;
-    use super::{APP_NAME, INPUT_MODES, USAGE, InputMode};
+    use super::{APP_NAME, INPUT_MODES, MAX_DEPTH_LIMIT, USAGE, InputMode,
+                parse_from_argv, validate_max_depth};
 
     #[test]
     fn input_modes_are_consistent() {
@@ -291,4 +429,42 @@ mod tests {
                 "Input mode '{}' is missing from usage string", mode);
         }
     }
+
+    #[test]
+    fn input_files() {
+        let opts = parse_from_argv(vec!["rh", "_", "--", "a.txt", "-"]);
+        assert_eq!(vec!["_"], opts.expressions);
+        assert_eq!(vec!["a.txt", "-"], opts.files);
+
+        let opts = parse_from_argv(vec!["rh", "--in", "a.txt", "--in", "b.txt", "_"]);
+        assert_eq!(vec!["_"], opts.expressions);
+        assert_eq!(vec!["a.txt", "b.txt"], opts.files);
+    }
+
+    #[test]
+    fn in_place() {
+        let opts = parse_from_argv(vec!["rh", "_", "--", "a.txt"]);
+        assert!(!opts.in_place);
+
+        let opts = parse_from_argv(vec!["rh", "--in-place", "_", "--", "a.txt"]);
+        assert!(opts.in_place);
+        assert_eq!(None, opts.backup_suffix);
+        assert_eq!(vec!["_"], opts.expressions);
+
+        let opts = parse_from_argv(vec!["rh", "--in-place=.bak", "_", "--", "a.txt"]);
+        assert!(opts.in_place);
+        assert_eq!(Some(".bak".to_owned()), opts.backup_suffix);
+        assert_eq!(vec!["_"], opts.expressions);
+    }
+
+    #[test]
+    fn max_depth() {
+        let opts = parse_from_argv(vec!["rh", "--max-depth", "1000", "_"]);
+        assert_eq!(Some(1000), opts.max_depth);
+
+        assert!(validate_max_depth(MAX_DEPTH_LIMIT.to_string()).is_ok());
+        assert!(validate_max_depth((MAX_DEPTH_LIMIT + 1).to_string()).is_err());
+        assert!(validate_max_depth("1000000".to_owned()).is_err());
+        assert!(validate_max_depth("-1".to_owned()).is_err());
+    }
 }
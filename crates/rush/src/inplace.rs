@@ -0,0 +1,198 @@
+//! Module implementing the editing of input files in place.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use input;
+
+
+/// Edit the file with given path in place, by passing its content through a function
+/// which writes the new content to given output.
+///
+/// The new content is first written to a temporary file in the same directory,
+/// which then atomically replaces the original file (retaining its permissions).
+/// If the function fails, the original file is left untouched.
+///
+/// Optionally, a copy of the original file is kept under a name with given suffix.
+///
+/// If the path is a symbolic link, it's the file it points to that gets edited,
+/// while the link itself is preserved.
+pub fn edit<F>(path: &str, backup_suffix: Option<&str>, func: F) -> io::Result<()>
+    where F: FnOnce(Box<Read>, &mut Write) -> io::Result<()>
+{
+    let input = try!(input::open_for_edit(path));
+    let permissions = try!(fs::metadata(path)).permissions();
+
+    let link_path = Path::new(path);
+    let path = try!(fs::canonicalize(link_path));
+    let path = path.as_path();
+    let (temp_path, mut temp_file) = try!(create_temp_file(path));
+    let result = func(input, &mut temp_file)
+        .and_then(|_| temp_file.sync_all())
+        .and_then(|_| fs::set_permissions(&temp_path, permissions))
+        .and_then(|_| match backup_suffix {
+            Some(suffix) => fs::copy(path, backup_path(link_path, suffix)).map(|_| ()),
+            None => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        debug!("Removing temporary file {} after an error", temp_path.display());
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+
+/// Create a new temporary file next to the one with given path.
+fn create_temp_file(path: &Path) -> io::Result<(PathBuf, File)> {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(String::new);
+    for i in 0.. {
+        let temp_path = path.with_file_name(format!(".{}.rh{}.tmp", name, i));
+        match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+/// Path of the backup for the file with given path.
+fn backup_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(suffix);
+    PathBuf::from(backup)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::{self, Read, Write};
+    use std::path::Path;
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tempdir::TempDir;
+
+    use super::edit;
+
+    #[test]
+    fn replaces_content() {
+        let dir = TempDir::new("rh").unwrap();
+        let path = write_file(&dir, "file.txt", b"foo\n");
+        edit(&path, None, uppercase).unwrap();
+        assert_eq!("FOO\n", read_file(&path));
+        assert_eq!(vec!["file.txt"], list_dir(&dir));
+    }
+
+    #[test]
+    fn backup() {
+        let dir = TempDir::new("rh").unwrap();
+        let path = write_file(&dir, "file.txt", b"foo\n");
+        edit(&path, Some(".bak"), uppercase).unwrap();
+        assert_eq!("FOO\n", read_file(&path));
+        assert_eq!("foo\n", read_file(&(path.clone() + ".bak")));
+    }
+
+    #[test]
+    fn error_leaves_file_intact() {
+        let dir = TempDir::new("rh").unwrap();
+        let path = write_file(&dir, "file.txt", b"foo\n");
+        let result = edit(&path, Some(".bak"), |_, output| {
+            try!(output.write_all(b"partial"));
+            Err(io::Error::new(io::ErrorKind::InvalidData, "eval error"))
+        });
+        assert!(result.is_err());
+        assert_eq!("foo\n", read_file(&path));
+        assert_eq!(vec!["file.txt"], list_dir(&dir));
+    }
+
+    #[test]
+    fn compressed_file_refused() {
+        let dir = TempDir::new("rh").unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::Default);
+        encoder.write_all(b"foo\n").unwrap();
+        let data = encoder.finish().unwrap();
+        let path = write_file(&dir, "file.gz", &data);
+
+        let error = edit(&path, None, uppercase).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+        let mut content = Vec::new();
+        File::open(&path).unwrap().read_to_end(&mut content).unwrap();
+        assert_eq!(data, content);
+        assert_eq!(vec!["file.gz"], list_dir(&dir));
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = TempDir::new("rh").unwrap();
+        let path = dir.path().join("missing.txt");
+        assert!(edit(path.to_str().unwrap(), None, uppercase).is_err());
+        assert!(list_dir(&dir).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions_kept() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("rh").unwrap();
+        let path = write_file(&dir, "script.sh", b"echo foo\n");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o751)).unwrap();
+        edit(&path, None, uppercase).unwrap();
+        assert_eq!("ECHO FOO\n", read_file(&path));
+        assert_eq!(0o751, fs::metadata(&path).unwrap().permissions().mode() & 0o777);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_target_edited() {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new("rh").unwrap();
+        let target = write_file(&dir, "target.txt", b"foo\n");
+        let link = dir.path().join("link.txt");
+        symlink(&target, &link).unwrap();
+        let link = link.to_str().unwrap().to_owned();
+
+        edit(&link, Some(".bak"), uppercase).unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!("FOO\n", read_file(&target));
+        assert_eq!("foo\n", read_file(&(link.clone() + ".bak")));
+        assert_eq!(vec!["link.txt", "link.txt.bak", "target.txt"], list_dir(&dir));
+    }
+
+    // Utility functions
+
+    fn uppercase(mut input: Box<Read>, output: &mut Write) -> io::Result<()> {
+        let mut content = String::new();
+        try!(input.read_to_string(&mut content));
+        output.write_all(content.to_uppercase().as_bytes())
+    }
+
+    fn write_file(dir: &TempDir, name: &str, data: &[u8]) -> String {
+        let path = dir.path().join(name);
+        File::create(&path).unwrap().write_all(data).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    fn read_file<P: AsRef<Path>>(path: P) -> String {
+        let mut content = String::new();
+        File::open(path).unwrap().read_to_string(&mut content).unwrap();
+        content
+    }
+
+    /// Sorted names of all the files in the directory, including hidden ones.
+    fn list_dir(dir: &TempDir) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir.path()).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+}
@@ -0,0 +1,218 @@
+//! Module for opening the input files given on the command line.
+//!
+//! Compressed files are transparently decompressed, based on the "magic" bytes
+//! they start with. This also applies to the standard input.
+
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read};
+
+use flate2::read::MultiGzDecoder;
+use zstd;
+
+
+/// Path that denotes the standard input.
+pub const STDIN: &'static str = "-";
+
+/// Leading bytes of the gzip format.
+const GZIP_MAGIC: &'static [u8] = &[0x1f, 0x8b];
+/// Leading bytes of the zstd format.
+const ZSTD_MAGIC: &'static [u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+/// How many leading bytes are needed to recognize any of the formats.
+const MAGIC_LEN: u64 = 4;
+
+
+/// Open the input file with given path (or the standard input, if the path is "-"),
+/// decompressing its content if necessary.
+pub fn open(path: &str) -> io::Result<Box<Read>> {
+    open_with_stdin(path, io::stdin())
+}
+
+/// Open the input file with given path for the purpose of editing it in place.
+///
+/// Compressed files are rejected, as the edited content wouldn't be compressed back.
+pub fn open_for_edit(path: &str) -> io::Result<Box<Read>> {
+    let reader = BufReader::new(try!(open_file(path)));
+    let (compression, reader) = try!(detect_compression(reader));
+    if compression.is_some() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+            format!("cannot edit compressed file {} in place", path)));
+    }
+    Ok(Box::new(reader))
+}
+
+
+/// Open the input file with given path, using given reader as the standard input.
+fn open_with_stdin<S: Read + 'static>(path: &str, stdin: S) -> io::Result<Box<Read>> {
+    let reader: Box<Read> = if path == STDIN {
+        Box::new(stdin)
+    } else {
+        Box::new(try!(open_file(path)))
+    };
+    decompress(BufReader::new(reader))
+}
+
+/// Wrap the reader so that its content is decompressed, if it's in a recognized format.
+/// Concatenated gzip streams (like from `cat a.gz b.gz`) are decompressed in their entirety.
+fn decompress<R: Read + 'static>(reader: R) -> io::Result<Box<Read>> {
+    let (compression, reader) = try!(detect_compression(reader));
+    match compression {
+        Some(Compression::Gzip) => {
+            debug!("Decompressing gzip input");
+            Ok(Box::new(try!(MultiGzDecoder::new(reader))))
+        },
+        Some(Compression::Zstd) => {
+            debug!("Decompressing zstd input");
+            Ok(Box::new(try!(zstd::Decoder::new(reader))))
+        },
+        None => Ok(Box::new(reader)),
+    }
+}
+
+/// Compression formats that are recognized in the input.
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+/// Reader which yields the leading bytes that have been read ahead, and then the rest.
+type Rewound<R> = io::Chain<Cursor<Vec<u8>>, R>;
+
+/// Detect the compression format (if any) of reader's content.
+/// Returns the format, and a reader that yields the entire content.
+///
+/// Since a read may return fewer bytes than requested (e.g. from a pipe),
+/// the header is read until it's complete, or the input ends.
+fn detect_compression<R: Read>(mut reader: R) -> io::Result<(Option<Compression>, Rewound<R>)> {
+    let mut header = Vec::with_capacity(MAGIC_LEN as usize);
+    try!(reader.by_ref().take(MAGIC_LEN).read_to_end(&mut header));
+    let compression = if header.starts_with(GZIP_MAGIC) { Some(Compression::Gzip) }
+                      else if header.starts_with(ZSTD_MAGIC) { Some(Compression::Zstd) }
+                      else { None };
+    Ok((compression, Cursor::new(header).chain(reader)))
+}
+
+fn open_file(path: &str) -> io::Result<File> {
+    File::open(path).map_err(|e| io::Error::new(
+        e.kind(), format!("cannot open input file {}: {}", path, e)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::{self, Cursor, Read, Write};
+
+    use flate2::Compression as GzCompression;
+    use flate2::write::GzEncoder;
+    use tempdir::TempDir;
+    use zstd;
+
+    use super::{STDIN, decompress, open, open_for_edit, open_with_stdin};
+
+    const TEXT: &'static str = "Lorem ipsum\ndolor sit amet\n";
+
+    #[test]
+    fn plain() {
+        assert_eq!(TEXT, read_all(decompress(Cursor::new(TEXT.as_bytes().to_vec()))));
+        assert_eq!("", read_all(decompress(io::empty())));
+        assert_eq!("ab", read_all(decompress(Cursor::new(b"ab".to_vec()))));
+    }
+
+    #[test]
+    fn gzip() {
+        let data = gzip_data(TEXT);
+        assert!(data.starts_with(super::GZIP_MAGIC));
+        assert_eq!(TEXT, read_all(decompress(Cursor::new(data))));
+    }
+
+    #[test]
+    fn gzip_multiple_members() {
+        let mut data = gzip_data("foo\n");
+        data.extend(gzip_data("bar\n"));
+        assert_eq!("foo\nbar\n", read_all(decompress(Cursor::new(data))));
+    }
+
+    #[test]
+    fn zstd() {
+        let data = zstd_data(TEXT);
+        assert!(data.starts_with(super::ZSTD_MAGIC));
+        assert_eq!(TEXT, read_all(decompress(Cursor::new(data))));
+    }
+
+    #[test]
+    fn short_reads() {
+        assert_eq!(TEXT, read_all(decompress(ByteByByte(Cursor::new(gzip_data(TEXT))))));
+        assert_eq!(TEXT, read_all(decompress(ByteByByte(Cursor::new(zstd_data(TEXT))))));
+        assert_eq!(TEXT, read_all(decompress(ByteByByte(Cursor::new(TEXT.as_bytes().to_vec())))));
+    }
+
+    #[test]
+    fn stdin() {
+        let stdin = Cursor::new(TEXT.as_bytes().to_vec());
+        assert_eq!(TEXT, read_all(open_with_stdin(STDIN, stdin)));
+        let stdin = Cursor::new(gzip_data(TEXT));
+        assert_eq!(TEXT, read_all(open_with_stdin(STDIN, stdin)));
+    }
+
+    #[test]
+    fn files() {
+        let dir = TempDir::new("rh").unwrap();
+        let plain = write_file(&dir, "plain.txt", TEXT.as_bytes());
+        let gz = write_file(&dir, "text.gz", &gzip_data(TEXT));
+        let zst = write_file(&dir, "text.zst", &zstd_data(TEXT));
+
+        assert_eq!(TEXT, read_all(open(&plain)));
+        assert_eq!(TEXT, read_all(open(&gz)));
+        assert_eq!(TEXT, read_all(open(&zst)));
+
+        let missing = dir.path().join("missing.txt");
+        assert!(open(missing.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn files_for_edit() {
+        let dir = TempDir::new("rh").unwrap();
+        let plain = write_file(&dir, "plain.txt", TEXT.as_bytes());
+        let gz = write_file(&dir, "text.gz", &gzip_data(TEXT));
+        let zst = write_file(&dir, "text.zst", &zstd_data(TEXT));
+
+        assert_eq!(TEXT, read_all(open_for_edit(&plain)));
+        assert_eq!(io::ErrorKind::InvalidInput, open_for_edit(&gz).err().unwrap().kind());
+        assert_eq!(io::ErrorKind::InvalidInput, open_for_edit(&zst).err().unwrap().kind());
+    }
+
+    // Utility functions
+
+    /// Reader which returns at most one byte at a time, like a slow pipe could.
+    struct ByteByByte<R>(R);
+    impl<R: Read> Read for ByteByByte<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = if buf.is_empty() { 0 } else { 1 };
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    fn gzip_data(text: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), GzCompression::Default);
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zstd_data(text: &str) -> Vec<u8> {
+        let mut encoder = zstd::Encoder::new(Vec::new(), 0).unwrap();
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn write_file(dir: &TempDir, name: &str, data: &[u8]) -> String {
+        let path = dir.path().join(name);
+        File::create(&path).unwrap().write_all(data).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    fn read_all(reader: io::Result<Box<Read>>) -> String {
+        let mut result = String::new();
+        reader.unwrap().read_to_string(&mut result).unwrap();
+        result
+    }
+}
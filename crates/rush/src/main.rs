@@ -3,26 +3,35 @@
 extern crate case;
 extern crate clap;
 extern crate conv;
+extern crate flate2;
 #[macro_use]
 extern crate log;
+extern crate zstd;
+#[cfg(test)]
+extern crate tempdir;
 
 extern crate rush;
 
 
 mod args;
+mod inplace;
+mod input;
 mod logging;
 mod rcfile;
 
 
+use std::cmp;
 use std::error::Error;  // for .cause() method
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use std::iter::repeat;
+use std::panic;
 use std::process::exit;
+use std::thread;
 
 use conv::TryFrom;
-use rush::Context;
+use rush::{Context, Value};
 
-use args::InputMode;
+use args::{InputMode, Options};
 
 
 fn main() {
@@ -30,18 +39,25 @@ fn main() {
 
     let opts = args::parse();
 
-    let before = opts.before.as_ref().map(|b| b as &str);
-    let exprs: Vec<&str> = opts.expressions.iter().map(|e| e as &str).collect();
-    let after = opts.after.as_ref().map(|a| a as &str);
-
-    match opts.input_mode {
+    match opts.input_mode.clone() {
         Some(mode) => {
-            if let Err(error) = process_input(mode, before, &exprs, after) {
+            let max_depth = opts.max_depth.unwrap_or(rush::DEFAULT_MAX_DEPTH);
+            let result = with_stack_for_depth(max_depth, move || {
+                let before = opts.before.as_ref().map(|b| b as &str);
+                let exprs: Vec<&str> = opts.expressions.iter().map(|e| e as &str).collect();
+                let after = opts.after.as_ref().map(|a| a as &str);
+                process_input(mode, &opts, before, &exprs, after)
+            });
+            if let Err(error) = result {
                 handle_error(error);
                 exit(1);
             }
         },
         None => {
+            let before = opts.before.as_ref().map(|b| b as &str);
+            let exprs: Vec<&str> = opts.expressions.iter().map(|e| e as &str).collect();
+            let after = opts.after.as_ref().map(|a| a as &str);
+
             if let Some(before) = before {
                 println!("--before expression:");
                 print_ast(before);
@@ -60,15 +76,49 @@ fn main() {
 }
 
 
-/// Process standard input through given expressions, writing results to stdout.
-fn process_input(mode: InputMode,
+/// Run the function on a separate thread, with a stack that's large enough
+/// for given maximum depth of nested function calls.
+///
+/// The limit on the depth is only useful if it's hit before the stack overflows,
+/// as the latter aborts the whole program rather than resulting in an error.
+fn with_stack_for_depth<F, T>(max_depth: usize, func: F) -> T
+    where F: FnOnce() -> T + Send + 'static, T: Send + 'static
+{
+    let stack_size = cmp::max(MIN_STACK_SIZE, max_depth.saturating_mul(STACK_SIZE_PER_CALL));
+    debug!("Evaluating on a thread with {} KiB of stack", stack_size / 1024);
+    let thread = thread::Builder::new().stack_size(stack_size).spawn(func)
+        .expect("failed to start the evaluation thread");
+    match thread.join() {
+        Ok(result) => result,
+        Err(panic) => panic::resume_unwind(panic),
+    }
+}
+
+/// Stack size of the evaluation thread (same as the default for the main thread on Linux).
+const MIN_STACK_SIZE: usize = 8 * 1024 * 1024;
+/// Generous estimate of the stack space used by a single call to a user-defined function.
+const STACK_SIZE_PER_CALL: usize = 64 * 1024;
+
+
+/// Process the input through given expressions, writing results to stdout.
+fn process_input(mode: InputMode, opts: &Options,
                  before: Option<&str>, exprs: &[&str], after: Option<&str>) -> io::Result<()> {
     // Prepare a Context for the processing.
     // This includes evaluating any "before" expression within it.
     let mut context = Context::new();
+    if let Some(max_depth) = opts.max_depth {
+        context.set_max_depth(max_depth);
+    }
+    context.set_coercion(opts.coercion);
+    context.set_graphemes(opts.graphemes);
+    // Raw input and in-place editing both should reproduce the input exactly
+    // where the expressions don't change it, line terminators included.
+    context.set_keep_line_endings(opts.in_place || opts.coercion.is_none());
     try!(rcfile::load_into(&mut context)
         .map_err(|err| io::Error::new(io::ErrorKind::InvalidData,
             format!("Error processing startup file: {}", err))));
+    // Strict mode only applies to expressions from the command line, not startup files.
+    context.set_strict(opts.strict);
     if let Some(before) = before {
         try!(rush::exec(before, &mut context));
     }
@@ -89,9 +139,9 @@ fn process_input(mode: InputMode,
         // all the input modes, so let's use that.
         let mut exprs = exprs.to_vec();
         exprs.push("0");
-        try!(apply_multi_ctx(mode, &mut context, &exprs, &mut io::sink()));
+        try!(apply_to_inputs(mode, opts, &mut context, &exprs, &mut io::sink()));
     } else {
-        try!(apply_multi_ctx(mode, &mut context, exprs, &mut io::stdout()));
+        try!(apply_to_inputs(mode, opts, &mut context, exprs, &mut io::stdout()));
     }
 
     // Evaluate the "after" expression, if provided, and return it as the result.
@@ -112,12 +162,71 @@ fn process_input(mode: InputMode,
     Ok(())
 }
 
-/// Apply the expressions to the standard input with given mode.
+/// Apply the expressions to all the input files in sequence
+/// (or just the standard input if there are none).
+fn apply_to_inputs(mode: InputMode, opts: &Options,
+                   context: &mut Context, exprs: &[&str], output: &mut Write) -> io::Result<()> {
+    let paths: Vec<&str> = if opts.files.is_empty() { vec![input::STDIN] }
+                           else { opts.files.iter().map(|f| f as &str).collect() };
+
+    if opts.in_place {
+        return edit_in_place(mode, opts, context, exprs, &paths);
+    }
+
+    // Whole input as a single string has to span all the files,
+    // so in this mode they are simply concatenated
+    // (and the file path is only known if there's just one).
+    if mode == InputMode::String {
+        if paths.len() == 1 {
+            context.set(rush::FILE_PATH, Value::String(paths[0].to_owned()));
+        }
+        let mut input: Box<Read> = Box::new(io::empty());
+        for path in paths {
+            input = Box::new(input.chain(try!(input::open(path))));
+        }
+        return apply_multi_ctx(mode, opts.filter, context, exprs, input, output);
+    }
+
+    for path in paths {
+        let input = try!(input::open(path));
+        context.set(rush::FILE_PATH, Value::String(path.to_owned()));
+        try!(apply_multi_ctx(mode.clone(), opts.filter, context, exprs, input, output));
+    }
+    Ok(())
+}
+
+/// Apply the expressions to each of the input files, replacing its content with the output.
+/// Processing stops at the first error, leaving the file that caused it unchanged.
+fn edit_in_place(mode: InputMode, opts: &Options,
+                 context: &mut Context, exprs: &[&str], paths: &[&str]) -> io::Result<()> {
+    if paths.contains(&input::STDIN) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+            "--in-place cannot be used when reading from standard input"));
+    }
+
+    let backup_suffix = opts.backup_suffix.as_ref().map(|s| s as &str);
+    for &path in paths {
+        info!("Editing file {} in place", path);
+        context.set(rush::FILE_PATH, Value::String(path.to_owned()));
+        try!(inplace::edit(path, backup_suffix, |input, output| {
+            apply_multi_ctx(mode.clone(), opts.filter, context, exprs, input, output)
+        }));
+    }
+    Ok(())
+}
+
+/// Apply the expressions to given input with given mode.
 /// This forms the bulk of the input processing.
 #[inline]
-fn apply_multi_ctx(mode: InputMode,
-                   context: &mut Context, exprs: &[&str], mut output: &mut Write) -> io::Result<()> {
+fn apply_multi_ctx(mode: InputMode, filter: bool,
+                   context: &mut Context, exprs: &[&str],
+                   input: Box<Read>, mut output: &mut Write) -> io::Result<()> {
+    if filter && mode != InputMode::Lines {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+            "--filter can only be used when processing input line by line"));
+    }
     let func: fn(_, _, _, _) -> _ = match mode {
+        InputMode::Lines if filter => rush::filter_lines_multi_ctx,
         InputMode::String => rush::apply_string_multi_ctx,
         InputMode::Lines => rush::map_lines_multi_ctx,
         InputMode::Words => rush::map_words_multi_ctx,
@@ -125,7 +234,7 @@ fn apply_multi_ctx(mode: InputMode,
         InputMode::Bytes => rush::map_bytes_multi_ctx,
         InputMode::Files => rush::map_files_multi_ctx,
     };
-    func(context, exprs, io::stdin(), &mut output)
+    func(context, exprs, input, &mut output)
 }
 
 /// Handle an error that occurred while processing the input.
@@ -156,3 +265,141 @@ fn print_ast(expr: &str) {
         Err(error) => { error!("{:?}", error); exit(1); },
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::{self, Read, Write};
+
+    use rush::{self, Context, Value};
+    use rush::value::IntegerRepr;
+    use tempdir::TempDir;
+
+    use args::{MAX_DEPTH_LIMIT, parse_from_argv};
+    use super::{apply_to_inputs, with_stack_for_depth};
+
+    #[test]
+    fn multiple_files_in_order() {
+        let dir = TempDir::new("rh").unwrap();
+        let first = write_file(&dir, "first.txt", "a\nb\n");
+        let second = write_file(&dir, "second.txt", "c\n");
+
+        let output = run(vec!["rh", "_", "--", &first, &second]);
+        assert_eq!("a\nb\nc\n", output);
+        let output = run(vec!["rh", "_n", "--", &first, &second]);
+        assert_eq!("0\n1\n2\n", output);
+
+        let output = run(vec!["rh", "--string", "_", "--", &first, &second]);
+        assert_eq!("a\nb\nc\n", output);
+    }
+
+    #[test]
+    fn file_path() {
+        let dir = TempDir::new("rh").unwrap();
+        let first = write_file(&dir, "first.txt", "a\n");
+        let second = write_file(&dir, "second.txt", "b\n");
+
+        let output = run(vec!["rh", "_p", "--", &first, &second]);
+        assert_eq!(format!("{}\n{}\n", first, second), output);
+
+        let output = run(vec!["rh", "--string", "_p", "--", &first]);
+        assert_eq!(format!("{}\n", first), output);
+    }
+
+    #[test]
+    fn in_place() {
+        let dir = TempDir::new("rh").unwrap();
+        let first = write_file(&dir, "first.txt", "a\nb\n");
+        let second = write_file(&dir, "second.txt", "c\n");
+
+        let output = run(vec!["rh", "--in-place=.bak", "upper(_)", "--", &first, &second]);
+        assert_eq!("", output);
+        assert_eq!("A\nB\n", read_file(&first));
+        assert_eq!("C\n", read_file(&second));
+        assert_eq!("a\nb\n", read_file(&(first.clone() + ".bak")));
+        assert_eq!("c\n", read_file(&(second.clone() + ".bak")));
+    }
+
+    #[test]
+    fn line_endings() {
+        let dir = TempDir::new("rh").unwrap();
+        let first = write_file(&dir, "first.txt", "x");
+        let second = write_file(&dir, "second.txt", "y\r\n");
+
+        let output = run(vec!["rh", "len(_)", "--", &first, &second]);
+        assert_eq!("1\n1\n", output);
+        let output = run(vec!["rh", "--raw", "_", "--", &first, &second]);
+        assert_eq!("xy\r\n", output);
+
+        run(vec!["rh", "--in-place", "upper(_)", "--", &first, &second]);
+        assert_eq!("X", read_file(&first));
+        assert_eq!("Y\r\n", read_file(&second));
+    }
+
+    #[test]
+    fn in_place_error() {
+        let dir = TempDir::new("rh").unwrap();
+        let path = write_file(&dir, "file.txt", "a\nb\n");
+
+        let result = try_run(vec!["rh", "--in-place", "int(_)", "--", &path]);
+        assert!(result.is_err());
+        assert_eq!("a\nb\n", read_file(&path));
+        let names: Vec<_> = fs::read_dir(dir.path()).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(vec!["file.txt"], names);
+    }
+
+    #[test]
+    fn in_place_stdin() {
+        let error = try_run(vec!["rh", "--in-place", "_", "--", "-"]).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+        let error = try_run(vec!["rh", "--in-place", "_"]).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+    }
+
+    #[test]
+    fn deep_recursion() {
+        // recursion that's deeper than the limit should be an error rather than
+        // a stack overflow, even at the largest --max-depth allowed
+        with_stack_for_depth(MAX_DEPTH_LIMIT, || {
+            let mut context = Context::new();
+            context.set_max_depth(MAX_DEPTH_LIMIT);
+            rush::exec("def down(n) = n == 0 ? 0 : 1 + down(n - 1)", &mut context).unwrap();
+
+            let deepest = format!("down({})", MAX_DEPTH_LIMIT - 1);
+            assert_eq!(Value::Integer(MAX_DEPTH_LIMIT as IntegerRepr - 1),
+                       rush::eval(&deepest, &mut context).unwrap());
+            let error = rush::eval("down(1000000)", &mut context).unwrap_err();
+            assert!(format!("{}", error).contains("recursion depth"));
+        });
+    }
+
+    // Utility functions
+
+    fn write_file(dir: &TempDir, name: &str, content: &str) -> String {
+        let path = dir.path().join(name);
+        File::create(&path).unwrap().write_all(content.as_bytes()).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    fn read_file(path: &str) -> String {
+        let mut content = String::new();
+        File::open(path).unwrap().read_to_string(&mut content).unwrap();
+        content
+    }
+
+    fn run(argv: Vec<&str>) -> String {
+        try_run(argv).unwrap()
+    }
+
+    fn try_run(argv: Vec<&str>) -> io::Result<String> {
+        let opts = parse_from_argv(argv);
+        let exprs: Vec<&str> = opts.expressions.iter().map(|e| e as &str).collect();
+        let mut context = Context::new();
+        let mut output = Vec::new();
+        try!(apply_to_inputs(opts.input_mode.unwrap(), &opts, &mut context, &exprs, &mut output));
+        Ok(String::from_utf8(output).unwrap())
+    }
+}